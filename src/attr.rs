@@ -0,0 +1,66 @@
+use std::collections::HashMap;
+
+use crate::{GitCommand, GitError};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AttrValue {
+    // `attr`
+    Set,
+    // `-attr`
+    Unset,
+    // not mentioned by any `.gitattributes`
+    Unspecified,
+    // `attr=value`
+    Value(String),
+}
+
+impl AttrValue {
+    fn parse(value: &str) -> AttrValue {
+        match value {
+            "set" => AttrValue::Set,
+            "unset" => AttrValue::Unset,
+            "unspecified" => AttrValue::Unspecified,
+            other => AttrValue::Value(String::from(other)),
+        }
+    }
+}
+
+// parses `check-attr -z`, `<path>\0<attr>\0<value>\0` triples
+pub(crate) fn parse_check_attr(out: &str) -> Result<HashMap<String, AttrValue>, GitError> {
+    let fields = out.split('\0').collect::<Vec<&str>>();
+    let mut attrs = HashMap::new();
+    // the output ends with a NUL, leaving one empty field after the last triple
+    for triple in fields.chunks(3).filter(|t| t.len() == 3) {
+        attrs.insert(String::from(triple[1]), AttrValue::parse(triple[2]));
+    }
+    if fields.len() % 3 != 1 {
+        return Err(GitError::Parse(format!(
+            "invalid check-attr output `{}`",
+            out
+        )));
+    }
+    Ok(attrs)
+}
+
+impl GitCommand {
+    // `check-attr -z <attrs> -- <path>`, how `.gitattributes` applies each of `attrs` to `path`.
+    // git rejects a check-attr without attributes, so that is caught before running it
+    pub fn check_attr(
+        &self,
+        attrs: &[&str],
+        path: &str,
+    ) -> Result<HashMap<String, AttrValue>, GitError> {
+        if attrs.is_empty() {
+            return Err(GitError::InvalidCommand(String::from(
+                "check-attr needs at least one attribute",
+            )));
+        }
+        let mut cmd = self.fresh();
+        cmd.subcommand("check-attr").arg("-z");
+        for attr in attrs {
+            cmd.arg(attr);
+        }
+        cmd.paths(&[path]);
+        parse_check_attr(&cmd.stdout()?)
+    }
+}
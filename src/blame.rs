@@ -0,0 +1,102 @@
+use std::path::PathBuf;
+
+use crate::{GitCommand, GitError};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BlameOpts {
+    // `--ignore-revs-file=<path>`, resolved by git against the command's directory
+    pub ignore_revs_file: Option<PathBuf>,
+    // `--ignore-rev=<rev>` for each, e.g. a mass reformatting commit
+    pub ignore_revs: Vec<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlameLine {
+    pub hash: String,
+    pub author_name: String,
+    pub author_email: String,
+    // author date, seconds since the epoch
+    pub timestamp: i64,
+    pub summary: String,
+    // line number in the commit that introduced it and in the blamed file
+    pub orig_line: u32,
+    pub final_line: u32,
+    pub content: String,
+}
+
+// parses `blame --line-porcelain`, where every line repeats its commit's headers and
+// the content line itself is the one starting with a tab
+pub(crate) fn parse_blame(out: &str) -> Result<Vec<BlameLine>, GitError> {
+    let mut lines = Vec::new();
+    let mut current: Option<BlameLine> = None;
+    for line in out.lines() {
+        if let Some(content) = line.strip_prefix('\t') {
+            let mut entry = current.take().ok_or_else(|| {
+                GitError::Parse(format!("blame content without header `{}`", line))
+            })?;
+            entry.content = String::from(content);
+            lines.push(entry);
+            continue;
+        }
+        let entry = match current.as_mut() {
+            Some(entry) => entry,
+            None => {
+                let invalid = || GitError::Parse(format!("invalid blame header `{}`", line));
+                let mut fields = line.split(' ');
+                let hash = fields.next().ok_or_else(invalid)?;
+                let orig_line = fields
+                    .next()
+                    .and_then(|n| n.parse().ok())
+                    .ok_or_else(invalid)?;
+                let final_line = fields
+                    .next()
+                    .and_then(|n| n.parse().ok())
+                    .ok_or_else(invalid)?;
+                current = Some(BlameLine {
+                    hash: String::from(hash),
+                    author_name: String::new(),
+                    author_email: String::new(),
+                    timestamp: 0,
+                    summary: String::new(),
+                    orig_line,
+                    final_line,
+                    content: String::new(),
+                });
+                continue;
+            }
+        };
+        let (key, value) = line.split_once(' ').unwrap_or((line, ""));
+        match key {
+            "author" => entry.author_name = String::from(value),
+            "author-mail" => {
+                entry.author_email =
+                    String::from(value.trim_start_matches('<').trim_end_matches('>'))
+            }
+            "author-time" => {
+                entry.timestamp = value
+                    .parse()
+                    .map_err(|_| GitError::Parse(format!("invalid timestamp `{}`", value)))?
+            }
+            "summary" => entry.summary = String::from(value),
+            _ => {}
+        }
+    }
+    Ok(lines)
+}
+
+impl GitCommand {
+    // who last touched each line of `path` at HEAD, skipping the commits `opts` ignores
+    pub fn blame(&self, path: &str, opts: BlameOpts) -> Result<Vec<BlameLine>, GitError> {
+        let mut cmd = self.fresh_parser();
+        cmd.subcommand("blame").arg("--line-porcelain");
+        if let Some(file) = &opts.ignore_revs_file {
+            // one token, so a path starting with `-` can't be taken for another option
+            cmd.arg(&format!("--ignore-revs-file={}", file.to_string_lossy()));
+        }
+        for rev in &opts.ignore_revs {
+            cmd.arg(&format!("--ignore-rev={}", rev));
+        }
+        cmd.paths(&[path]);
+        parse_blame(&cmd.stdout()?)
+    }
+}
@@ -0,0 +1,256 @@
+use crate::{trim_output, GitCommand, GitError, Trim};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BranchTracking {
+    pub name: String,
+    pub upstream: Option<String>,
+    pub ahead: usize,
+    pub behind: usize,
+    // the upstream is configured but its remote-tracking branch no longer exists
+    pub gone: bool,
+}
+
+// parses `%(upstream:track)`, e.g. `[ahead 1, behind 2]` or `[gone]`
+fn parse_track(track: &str) -> Result<(usize, usize, bool), GitError> {
+    let invalid = || GitError::Parse(format!("invalid tracking field `{}`", track));
+    if track.is_empty() {
+        return Ok((0, 0, false));
+    }
+    let inner = track
+        .strip_prefix('[')
+        .and_then(|t| t.strip_suffix(']'))
+        .ok_or_else(invalid)?;
+    if inner == "gone" {
+        return Ok((0, 0, true));
+    }
+    let (mut ahead, mut behind) = (0, 0);
+    for part in inner.split(", ") {
+        match part.split_once(' ') {
+            Some(("ahead", n)) => ahead = n.parse().map_err(|_| invalid())?,
+            Some(("behind", n)) => behind = n.parse().map_err(|_| invalid())?,
+            _ => return Err(invalid()),
+        }
+    }
+    Ok((ahead, behind, false))
+}
+
+// plain `git branch` output: strips the `* ` (current) and `+ ` (checked out in another
+// worktree) markers and skips the `(HEAD detached at ...)` entry
+pub(crate) fn parse_branch_list(out: &str) -> Vec<String> {
+    out.lines()
+        .map(|line| line.get(2..).unwrap_or("").trim())
+        .filter(|name| !name.is_empty() && !name.starts_with('('))
+        // `remotes/origin/HEAD -> origin/main` when listing with -a
+        .map(|name| name.split(" -> ").next().unwrap_or(name))
+        .map(String::from)
+        .collect()
+}
+
+impl GitCommand {
+    // ahead/behind counts of every local branch against its upstream, in one git call
+    pub fn branch_tracking_status(&self) -> Result<Vec<BranchTracking>, GitError> {
+        let out = self
+            .fresh()
+            .for_each_ref()
+            .arg("--format=%(refname:short)%00%(upstream:short)%00%(upstream:track)")
+            .arg("refs/heads")
+            .stdout()?;
+        out.lines()
+            .filter(|line| !line.is_empty())
+            .map(|line| {
+                let fields = line.split('\0').collect::<Vec<&str>>();
+                if fields.len() != 3 {
+                    return Err(GitError::Parse(format!("invalid branch record `{}`", line)));
+                }
+                let (ahead, behind, gone) = parse_track(fields[2])?;
+                Ok(BranchTracking {
+                    name: String::from(fields[0]),
+                    upstream: (!fields[1].is_empty()).then(|| String::from(fields[1])),
+                    ahead,
+                    behind,
+                    gone,
+                })
+            })
+            .collect()
+    }
+
+    // `None` when HEAD is detached
+    pub fn current_branch(&self) -> Result<Option<String>, GitError> {
+        let name = self.fresh().branch().arg("--show-current").run_trimmed()?;
+        Ok(if name.is_empty() { None } else { Some(name) })
+    }
+
+    // the remote-tracking branch `branch` follows, from `rev-parse --abbrev-ref <branch>@{upstream}`.
+    // `None` without an upstream, and also when the upstream's remote-tracking branch is
+    // gone, e.g. pruned after the remote branch was deleted
+    pub fn upstream_of(&self, branch: &str) -> Result<Option<String>, GitError> {
+        let mut cmd = self.fresh();
+        cmd.rev_parse()
+            .arg("--abbrev-ref")
+            .arg(&format!("{}@{{upstream}}", branch));
+        let output = cmd.execute()?;
+        if output.success() {
+            return Ok(Some(trim_output(
+                String::from_utf8(output.stdout)?,
+                Trim::Newline,
+            )));
+        }
+        // rev-parse fails the same way for a missing or gone upstream and a broken repo,
+        // so tell them apart by what for-each-ref knows about the tracking
+        let tracking = self
+            .fresh()
+            .for_each_ref()
+            .arg("--format=%(upstream) %(upstream:track)")
+            .arg(&format!("refs/heads/{}", branch))
+            .stdout()?;
+        let (upstream, track) = tracking
+            .trim()
+            .split_once(' ')
+            .unwrap_or((tracking.trim(), ""));
+        if upstream.is_empty() || track == "[gone]" {
+            Ok(None)
+        } else {
+            Err(cmd.failure(&output))
+        }
+    }
+
+    // `switch --detach <rev>`
+    pub fn switch_detach(&mut self, rev: &str) -> &mut GitCommand {
+        self.switch().arg("--detach").arg(rev)
+    }
+
+    // `switch -`, back to the previously checked out branch
+    pub fn switch_previous(&mut self) -> &mut GitCommand {
+        self.switch().arg("-")
+    }
+
+    // `checkout --orphan <name>`, a branch with no history whose first commit will have no
+    // parent. the index and working tree keep the previous branch's files, so clear them
+    // (e.g. `rm -rf .` via `rm_paths`) before committing if the branch should start empty
+    pub fn checkout_orphan(&mut self, name: &str) -> &mut GitCommand {
+        self.checkout().arg("--orphan").branch_name(name)
+    }
+
+    // `switch --orphan <name>`, which unlike `checkout_orphan` also empties the index and
+    // removes the tracked files
+    pub fn switch_orphan(&mut self, name: &str) -> &mut GitCommand {
+        self.switch().arg("--orphan").branch_name(name)
+    }
+
+    // `switch -c <name> [<start_point>]` then `push -u <remote> <name>`. if the push
+    // fails the local branch is left in place, and the error says so
+    pub fn publish_branch(
+        &self,
+        name: &str,
+        remote: &str,
+        start_point: Option<&str>,
+    ) -> Result<(), GitError> {
+        let mut create = self.fresh();
+        create.switch().arg("-c").arg(name);
+        if let Some(start_point) = start_point {
+            create.arg(start_point);
+        }
+        create.output().map_err(|e| GitError::StepFailed {
+            step: format!("creating branch {}", name),
+            source: Box::new(e),
+        })?;
+        self.fresh()
+            .push()
+            .arg("-u")
+            .arg(remote)
+            .arg(name)
+            .output()
+            .map_err(|e| GitError::StepFailed {
+                step: format!(
+                    "pushing {} to {} (the local branch was created but not published)",
+                    name, remote
+                ),
+                source: Box::new(e),
+            })?;
+        Ok(())
+    }
+
+    fn branches_filtered(&self, filter: &str, target: &str) -> Result<Vec<String>, GitError> {
+        let out = self
+            .fresh()
+            .branch()
+            .arg("--no-color")
+            .arg(filter)
+            .arg(target)
+            .stdout()?;
+        Ok(parse_branch_list(&out)
+            .into_iter()
+            .filter(|name| name != target)
+            .collect())
+    }
+
+    // local branches already merged into `target`, i.e. safe to delete
+    pub fn branches_merged_into(&self, target: &str) -> Result<Vec<String>, GitError> {
+        self.branches_filtered("--merged", target)
+    }
+
+    // local branches with commits `target` doesn't have
+    pub fn branches_not_merged_into(&self, target: &str) -> Result<Vec<String>, GitError> {
+        self.branches_filtered("--no-merged", target)
+    }
+
+    // local and remote-tracking branches (`remotes/<remote>/<name>`) whose history
+    // includes `rev`
+    pub fn branches_containing(&self, rev: &str) -> Result<Vec<String>, GitError> {
+        let out = self
+            .fresh()
+            .branch()
+            .arg("-a")
+            .arg("--no-color")
+            .arg("--contains")
+            .arg(rev)
+            .stdout()?;
+        Ok(parse_branch_list(&out))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_track_counts() {
+        assert_eq!(parse_track("").unwrap(), (0, 0, false));
+        assert_eq!(parse_track("[ahead 3]").unwrap(), (3, 0, false));
+        assert_eq!(parse_track("[behind 2]").unwrap(), (0, 2, false));
+        assert_eq!(parse_track("[ahead 1, behind 4]").unwrap(), (1, 4, false));
+        assert_eq!(parse_track("[gone]").unwrap(), (0, 0, true));
+    }
+
+    #[test]
+    fn parse_track_rejects_garbage() {
+        assert!(parse_track("ahead 1").is_err());
+        assert!(parse_track("[ahead x]").is_err());
+        assert!(parse_track("[sideways 1]").is_err());
+    }
+
+    #[test]
+    fn switch_detach_and_previous_tokens() {
+        let mut cmd = GitCommand::create(false);
+        assert_eq!(
+            cmd.switch_detach("v1.0").dry_run(),
+            ["git", "switch", "--detach", "v1.0"]
+        );
+        let mut cmd = GitCommand::create(false);
+        assert_eq!(cmd.switch_previous().dry_run(), ["git", "switch", "-"]);
+    }
+
+    #[test]
+    fn orphan_tokens() {
+        let mut cmd = GitCommand::create(false);
+        assert_eq!(
+            cmd.checkout_orphan("gh-pages").dry_run(),
+            ["git", "checkout", "--orphan", "gh-pages"]
+        );
+        let mut cmd = GitCommand::create(false);
+        assert_eq!(
+            cmd.switch_orphan("docs").dry_run(),
+            ["git", "switch", "--orphan", "docs"]
+        );
+    }
+}
@@ -0,0 +1,246 @@
+use std::path::{Path, PathBuf};
+
+use crate::{GitCommand, GitError};
+
+// git resolves message files against its own working directory, which `cwd` may have
+// changed, so relative paths are anchored to this process's directory first
+fn anchored(path: &Path) -> PathBuf {
+    if path.is_relative() {
+        if let Ok(dir) = std::env::current_dir() {
+            return dir.join(path);
+        }
+    }
+    path.to_path_buf()
+}
+
+/* a commit's signature as reported by `verify-commit --raw` */
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct SignatureStatus {
+    pub signed: bool,
+    // `None` when the signature couldn't be checked, e.g. the public key is missing
+    pub valid: Option<bool>,
+    pub signer: Option<String>,
+    pub key_id: Option<String>,
+}
+
+// parses the `[GNUPG:] <KEYWORD> <args>` status lines gpg writes to stderr
+pub(crate) fn parse_signature_status(raw: &str) -> SignatureStatus {
+    let mut status = SignatureStatus::default();
+    for line in raw.lines() {
+        let Some(line) = line.strip_prefix("[GNUPG:] ") else {
+            continue;
+        };
+        let (keyword, args) = line.split_once(' ').unwrap_or((line, ""));
+        let valid = match keyword {
+            "GOODSIG" => Some(true),
+            "BADSIG" | "EXPSIG" | "EXPKEYSIG" | "REVKEYSIG" => Some(false),
+            "ERRSIG" => None,
+            _ => continue,
+        };
+        let (key_id, signer) = args.split_once(' ').unwrap_or((args, ""));
+        status.signed = true;
+        status.valid = valid;
+        status.key_id = Some(String::from(key_id)).filter(|k| !k.is_empty());
+        // ERRSIG's remaining fields are algorithm numbers rather than a user id
+        if keyword != "ERRSIG" && !signer.is_empty() {
+            status.signer = Some(String::from(signer));
+        }
+    }
+    status
+}
+
+impl GitCommand {
+    // `commit -F <path>`, or `commit --template=<path>` to open the editor on it
+    pub fn commit_from_file(&mut self, path: &Path, edit: bool) -> &mut GitCommand {
+        let path = anchored(path).to_string_lossy().into_owned();
+        if edit {
+            self.commit().arg(&format!("--template={}", path))
+        } else {
+            self.commit().arg("-F").arg(&path)
+        }
+    }
+
+    // `commit -m <message> --date=<author_date>` with `GIT_COMMITTER_DATE` exported, since
+    // the committer date has no flag. dates are passed through verbatim, so anything git
+    // accepts (RFC 2822, ISO 8601, `@<epoch>`) works. the committer date is only exported
+    // for this commit, `then` doesn't carry it over
+    pub fn commit_with_dates(
+        &mut self,
+        message: &str,
+        author_date: &str,
+        committer_date: &str,
+    ) -> &mut GitCommand {
+        self.command_env("GIT_COMMITTER_DATE", committer_date);
+        self.commit()
+            .arg("-m")
+            .arg(message)
+            .arg(&format!("--date={}", author_date))
+    }
+
+    // signs the commit or tag being built, `-S[<key_id>]` for commit and `-s`/`-u <key_id>`
+    // for tag, optionally through a specific gpg binary (`-c gpg.program=<program>`).
+    // point a CI keyring at it with `env("GNUPGHOME", ...)`. anything other than a
+    // commit or tag makes the command fail validation
+    pub fn sign_with(&mut self, key_id: Option<&str>, program: Option<&str>) -> &mut GitCommand {
+        match (self.current_subcommand(), key_id) {
+            (Some("commit"), Some(key)) => self.arg(&format!("-S{}", key)),
+            (Some("commit"), None) => self.arg("-S"),
+            (Some("tag"), Some(key)) => self.arg("-u").arg(key),
+            (Some("tag"), None) => self.arg("-s"),
+            (other, _) => {
+                let msg = format!("signing isn't supported by {}", other.unwrap_or("git"));
+                return self.invalidate(msg);
+            }
+        };
+        if let Some(program) = program {
+            self.global_config("gpg.program", program);
+        }
+        self
+    }
+
+    // `--no-verify`, skipping the pre-commit/commit-msg or pre-push hooks. only commit
+    // and push accept it, anything else fails validation
+    pub fn no_verify(&mut self) -> &mut GitCommand {
+        match self.current_subcommand() {
+            Some("commit") | Some("push") => self.arg("--no-verify"),
+            other => {
+                let msg = format!("--no-verify isn't supported by {}", other.unwrap_or("git"));
+                self.invalidate(msg)
+            }
+        }
+    }
+
+    // `commit --amend`, folding whatever is staged into HEAD, keeping its message with `no_edit`
+    pub fn amend_staged(&mut self, no_edit: bool) -> &mut GitCommand {
+        self.commit().arg("--amend");
+        if no_edit {
+            self.arg("--no-edit");
+        }
+        self
+    }
+
+    // `commit --fixup=<target>`, to be squashed into `target` by `rebase_autosquash`
+    pub fn commit_fixup(&mut self, target: &str) -> &mut GitCommand {
+        self.commit().arg(&format!("--fixup={}", target))
+    }
+
+    // `rebase -i --autosquash <upstream>`. the interactive todo list would normally open
+    // an editor, so `GIT_SEQUENCE_EDITOR=true` is exported, for this rebase only, to accept
+    // it as generated
+    pub fn rebase_autosquash(&mut self, upstream: &str) -> &mut GitCommand {
+        self.command_env("GIT_SEQUENCE_EDITOR", "true");
+        self.rebase()
+            .arg("--interactive")
+            .arg("--autosquash")
+            .branch_name(upstream)
+    }
+
+    // `verify-commit --raw <rev>`. git exits 1 for unsigned and badly signed commits alike,
+    // so the outcome is read from gpg's status lines instead
+    pub fn verify_commit(&self, rev: &str) -> Result<SignatureStatus, GitError> {
+        self.require_commit(rev)?;
+        let mut cmd = self.fresh();
+        cmd.subcommand("verify-commit").arg("--raw").arg(rev);
+        let output = cmd.execute()?;
+        match output.code {
+            Some(0) | Some(1) => Ok(parse_signature_status(&String::from_utf8_lossy(
+                &output.stderr,
+            ))),
+            _ => Err(cmd.failure(&output)),
+        }
+    }
+
+    // `format-patch -1 --stdout <rev>`, one commit as an mbox patch ready for `git am`.
+    // format-patch silently skips merges, so `-1` on a merge would export one of its
+    // parents' commits instead; merges are refused up front
+    pub fn commit_patch(&self, rev: &str) -> Result<String, GitError> {
+        self.require_commit(rev)?;
+        let is_merge = self
+            .fresh()
+            .rev_parse()
+            .arg("--verify")
+            .arg("--quiet")
+            .arg(&format!("{}^2", rev))
+            .execute()?
+            .success();
+        if is_merge {
+            return Err(GitError::InvalidCommand(format!(
+                "{} is a merge commit, which format-patch can't export",
+                rev
+            )));
+        }
+        let mut cmd = self.fresh();
+        cmd.subcommand("format-patch")
+            .arg("-1")
+            .arg("--stdout")
+            .arg("--no-color")
+            .arg(rev);
+        cmd.stdout()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_good_signature() {
+        let raw = "[GNUPG:] NEWSIG\n\
+                   [GNUPG:] KEY_CONSIDERED 0123456789ABCDEF0123456789ABCDEF01234567 0\n\
+                   [GNUPG:] GOODSIG 89ABCDEF01234567 Ada Lovelace <ada@example.com>\n\
+                   [GNUPG:] VALIDSIG 0123456789ABCDEF0123456789ABCDEF01234567\n";
+        assert_eq!(
+            parse_signature_status(raw),
+            SignatureStatus {
+                signed: true,
+                valid: Some(true),
+                signer: Some(String::from("Ada Lovelace <ada@example.com>")),
+                key_id: Some(String::from("89ABCDEF01234567")),
+            }
+        );
+    }
+
+    #[test]
+    fn parse_bad_and_unchecked_signatures() {
+        let bad = parse_signature_status("[GNUPG:] BADSIG 89ABCDEF01234567 Ada Lovelace\n");
+        assert_eq!(bad.valid, Some(false));
+        assert_eq!(bad.signer.as_deref(), Some("Ada Lovelace"));
+        // a missing public key leaves the signature unchecked and the signer unknown
+        let missing =
+            parse_signature_status("[GNUPG:] ERRSIG 89ABCDEF01234567 1 8 00 1700000000 9\n");
+        assert!(missing.signed);
+        assert_eq!(missing.valid, None);
+        assert_eq!(missing.signer, None);
+        assert_eq!(missing.key_id.as_deref(), Some("89ABCDEF01234567"));
+        assert_eq!(parse_signature_status(""), SignatureStatus::default());
+    }
+
+    #[test]
+    fn committer_date_is_not_reused() {
+        let mut cmd = GitCommand::create(false);
+        cmd.env("GNUPGHOME", "/keys")
+            .commit_with_dates("first", "@1700000000", "@1700000001");
+        assert_eq!(
+            cmd.to_shell_script(),
+            "GNUPGHOME=/keys GIT_COMMITTER_DATE=@1700000001 git commit -m first \
+             --date=@1700000000\n"
+        );
+        cmd.then().commit().arg("-m").arg("second");
+        assert_eq!(
+            cmd.to_shell_script(),
+            "GNUPGHOME=/keys git commit -m second\n"
+        );
+    }
+
+    #[test]
+    fn sequence_editor_is_not_reused() {
+        let mut cmd = GitCommand::create(false);
+        cmd.rebase_autosquash("main");
+        assert_eq!(
+            cmd.to_shell_script(),
+            "GIT_SEQUENCE_EDITOR=true git rebase --interactive --autosquash main\n"
+        );
+        cmd.then().rebase().arg("-i").arg("main");
+        assert_eq!(cmd.to_shell_script(), "git rebase -i main\n");
+    }
+}
@@ -0,0 +1,18 @@
+use crate::{trim_output, GitCommand, GitError, Trim};
+
+impl GitCommand {
+    // `config --get <key>`, `None` when the key isn't set
+    pub fn config_get(&self, key: &str) -> Result<Option<String>, GitError> {
+        let mut cmd = self.fresh();
+        cmd.config().arg("--get").arg(key);
+        let output = cmd.execute()?;
+        match output.code {
+            Some(0) => Ok(Some(trim_output(
+                String::from_utf8(output.stdout)?,
+                Trim::Newline,
+            ))),
+            Some(1) => Ok(None),
+            _ => Err(cmd.failure(&output)),
+        }
+    }
+}
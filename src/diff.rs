@@ -0,0 +1,414 @@
+use crate::{GitCommand, GitError};
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DiffOpts {
+    // lines of context, `-U<n>`
+    pub unified: Option<usize>,
+    // `-w`
+    pub ignore_whitespace: bool,
+    // `--stat` instead of the patch
+    pub stat_only: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DiffLine {
+    Added(String),
+    Removed(String),
+    Context(String),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Hunk {
+    pub old_start: u32,
+    pub old_lines: u32,
+    pub new_start: u32,
+    pub new_lines: u32,
+    pub lines: Vec<DiffLine>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileDiff {
+    // `None` for the missing side of an added or deleted file
+    pub old_path: Option<String>,
+    pub new_path: Option<String>,
+    pub hunks: Vec<Hunk>,
+}
+
+// the status letter of a `--raw` diff line
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Added,
+    Copied,
+    Deleted,
+    Modified,
+    Renamed,
+    TypeChanged,
+    Unmerged,
+    Unknown,
+}
+
+impl ChangeKind {
+    fn from_letter(letter: char) -> Option<ChangeKind> {
+        match letter {
+            'A' => Some(ChangeKind::Added),
+            'C' => Some(ChangeKind::Copied),
+            'D' => Some(ChangeKind::Deleted),
+            'M' => Some(ChangeKind::Modified),
+            'R' => Some(ChangeKind::Renamed),
+            'T' => Some(ChangeKind::TypeChanged),
+            'U' => Some(ChangeKind::Unmerged),
+            'X' => Some(ChangeKind::Unknown),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FileChange {
+    pub status: ChangeKind,
+    // `None` for the missing side of an added or deleted file
+    pub old_path: Option<String>,
+    pub new_path: Option<String>,
+    // percentage score of a rename or copy, e.g. 100 for an exact move
+    pub similarity: Option<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitChanges {
+    pub hash: String,
+    pub changes: Vec<FileChange>,
+}
+
+// parses `log --raw -z --pretty=format:%x1e%H`: each commit is its hash and a newline, then
+// NUL separated `:<modes> <oids> <status>` fields each followed by one path, or two for
+// renames and copies
+pub(crate) fn parse_raw_log(out: &str) -> Result<Vec<CommitChanges>, GitError> {
+    let mut commits = Vec::new();
+    for record in out.split('\x1e').filter(|r| !r.is_empty()) {
+        // a commit without changes is just its hash and the terminator
+        let record = record.trim_end_matches('\0');
+        let (hash, raw) = record.split_once('\n').unwrap_or((record, ""));
+        let mut fields = raw.split('\0').filter(|f| !f.is_empty());
+        let mut changes = Vec::new();
+        while let Some(meta) = fields.next() {
+            let invalid = || GitError::Parse(format!("invalid raw diff line `{}`", meta));
+            let code = meta
+                .strip_prefix(':')
+                .and_then(|m| m.split(' ').nth(4))
+                .ok_or_else(invalid)?;
+            let letter = code.chars().next().ok_or_else(invalid)?;
+            let status = ChangeKind::from_letter(letter).ok_or_else(invalid)?;
+            let similarity = match code.get(1..) {
+                Some(score) if !score.is_empty() => Some(score.parse().map_err(|_| invalid())?),
+                _ => None,
+            };
+            let path = fields.next().map(String::from).ok_or_else(invalid)?;
+            let (old_path, new_path) = match status {
+                ChangeKind::Renamed | ChangeKind::Copied => {
+                    let new = fields.next().map(String::from).ok_or_else(invalid)?;
+                    (Some(path), Some(new))
+                }
+                ChangeKind::Added => (None, Some(path)),
+                ChangeKind::Deleted => (Some(path), None),
+                _ => (Some(path.clone()), Some(path)),
+            };
+            changes.push(FileChange {
+                status,
+                old_path,
+                new_path,
+                similarity,
+            });
+        }
+        commits.push(CommitChanges {
+            hash: String::from(hash),
+            changes,
+        });
+    }
+    Ok(commits)
+}
+
+// `-12,3` or `+4` (a missing count means one line)
+fn parse_range(range: &str) -> Option<(u32, u32)> {
+    let (start, lines) = range.split_once(',').unwrap_or((range, "1"));
+    Some((start.parse().ok()?, lines.parse().ok()?))
+}
+
+// `@@ -a,b +c,d @@ optional section heading`
+fn parse_hunk_header(line: &str) -> Result<Hunk, GitError> {
+    let invalid = || GitError::Parse(format!("invalid hunk header `{}`", line));
+    let mut parts = line.strip_prefix("@@ ").ok_or_else(invalid)?.split(' ');
+    let old = parts.next().and_then(|p| p.strip_prefix('-'));
+    let new = parts.next().and_then(|p| p.strip_prefix('+'));
+    let ((old_start, old_lines), (new_start, new_lines)) = old
+        .and_then(parse_range)
+        .zip(new.and_then(parse_range))
+        .ok_or_else(invalid)?;
+    Ok(Hunk {
+        old_start,
+        old_lines,
+        new_start,
+        new_lines,
+        lines: Vec::new(),
+    })
+}
+
+// `a/path` -> `path`, `/dev/null` -> None
+fn header_path(path: &str, prefix: &str) -> Option<String> {
+    if path == "/dev/null" {
+        return None;
+    }
+    Some(String::from(path.strip_prefix(prefix).unwrap_or(path)))
+}
+
+// parses unified `git diff` output into files and hunks
+pub(crate) fn parse_diff(out: &str) -> Result<Vec<FileDiff>, GitError> {
+    let mut files: Vec<FileDiff> = Vec::new();
+    // lines still expected on each side of the current hunk
+    let (mut old_left, mut new_left) = (0u32, 0u32);
+    // not `lines()`, which would also eat the `\r` of CRLF content
+    for line in out.split_terminator('\n') {
+        if old_left > 0 || new_left > 0 {
+            let too_long = || GitError::Parse(String::from("hunk longer than its header"));
+            let hunk = files
+                .last_mut()
+                .and_then(|f| f.hunks.last_mut())
+                .ok_or_else(|| GitError::Parse(String::from("hunk lines without a hunk header")))?;
+            let text = String::from(line.get(1..).unwrap_or(""));
+            match line.chars().next() {
+                Some('+') => {
+                    new_left = new_left.checked_sub(1).ok_or_else(too_long)?;
+                    hunk.lines.push(DiffLine::Added(text));
+                }
+                Some('-') => {
+                    old_left = old_left.checked_sub(1).ok_or_else(too_long)?;
+                    hunk.lines.push(DiffLine::Removed(text));
+                }
+                Some(' ') | None => {
+                    old_left = old_left.checked_sub(1).ok_or_else(too_long)?;
+                    new_left = new_left.checked_sub(1).ok_or_else(too_long)?;
+                    hunk.lines.push(DiffLine::Context(text));
+                }
+                Some('\\') => {}
+                _ => return Err(GitError::Parse(format!("invalid hunk line `{}`", line))),
+            }
+            continue;
+        }
+        if let Some(header) = line.strip_prefix("diff --git ") {
+            // only a fallback, the ---/+++ or rename lines are unambiguous
+            let (old, new) = header.split_once(" b/").unwrap_or((header, header));
+            files.push(FileDiff {
+                old_path: header_path(old, "a/"),
+                new_path: Some(String::from(new)),
+                hunks: Vec::new(),
+            });
+            continue;
+        }
+        let file = match files.last_mut() {
+            Some(file) => file,
+            None => continue,
+        };
+        if let Some(path) = line.strip_prefix("--- ") {
+            file.old_path = header_path(path, "a/");
+        } else if let Some(path) = line.strip_prefix("+++ ") {
+            file.new_path = header_path(path, "b/");
+        } else if let Some(path) = line.strip_prefix("rename from ") {
+            file.old_path = Some(String::from(path));
+        } else if let Some(path) = line.strip_prefix("rename to ") {
+            file.new_path = Some(String::from(path));
+        } else if line.starts_with("new file mode") {
+            file.old_path = None;
+        } else if line.starts_with("deleted file mode") {
+            file.new_path = None;
+        } else if line.starts_with("@@ ") {
+            let hunk = parse_hunk_header(line)?;
+            old_left = hunk.old_lines;
+            new_left = hunk.new_lines;
+            file.hunks.push(hunk);
+        }
+    }
+    Ok(files)
+}
+
+impl GitCommand {
+    // unified diff between two tree-ishes, with color forced off
+    pub fn diff_refs(&self, from: &str, to: &str, opts: DiffOpts) -> Result<String, GitError> {
+        let mut cmd = self.fresh_parser();
+        cmd.diff().arg("--no-color").arg("--no-ext-diff");
+        if let Some(n) = opts.unified {
+            cmd.arg(&format!("-U{}", n));
+        }
+        if opts.ignore_whitespace {
+            cmd.arg("-w");
+        }
+        if opts.stat_only {
+            cmd.arg("--stat");
+        }
+        cmd.arg(from).arg(to).stdout()
+    }
+
+    // the diff between two tree-ishes as structured hunks, optionally for a single path
+    pub fn diff_hunks(
+        &self,
+        from: &str,
+        to: &str,
+        path: Option<&str>,
+    ) -> Result<Vec<FileDiff>, GitError> {
+        let mut cmd = self.fresh_parser();
+        // explicit prefixes, so `diff.noprefix` or `diff.mnemonicPrefix` can't change the headers
+        cmd.diff()
+            .arg("--no-color")
+            .arg("--no-ext-diff")
+            .arg("--src-prefix=a/")
+            .arg("--dst-prefix=b/")
+            .arg(from)
+            .arg(to);
+        if let Some(path) = path {
+            cmd.paths(&[path]);
+        }
+        parse_diff(&cmd.stdout()?)
+    }
+
+    // `diff <from_rev> <to_rev> -- <path>`, how a single file changed between two revisions
+    pub fn file_diff(&self, from_rev: &str, to_rev: &str, path: &str) -> Result<String, GitError> {
+        let mut cmd = self.fresh_parser();
+        cmd.diff()
+            .arg("--no-color")
+            .arg("--no-ext-diff")
+            .arg(from_rev)
+            .arg(to_rev)
+            .paths(&[path])
+            .stdout()
+    }
+
+    // `diff --cached`, exactly what the next commit would record
+    pub fn staged_diff(&self) -> Result<String, GitError> {
+        let mut cmd = self.fresh_parser();
+        cmd.diff()
+            .arg("--cached")
+            .arg("--no-color")
+            .arg("--no-ext-diff")
+            .stdout()
+    }
+
+    // `diff --cached --stat`
+    pub fn staged_patch_stat(&self) -> Result<String, GitError> {
+        let mut cmd = self.fresh_parser();
+        cmd.diff()
+            .arg("--cached")
+            .arg("--no-color")
+            .arg("--stat")
+            .stdout()
+    }
+
+    // the files each commit in `range` touched, with renames and copies detected (`-M`)
+    // and their similarity scores
+    pub fn changes_in_range(&self, range: &str) -> Result<Vec<CommitChanges>, GitError> {
+        let mut cmd = self.fresh();
+        cmd.log()
+            .arg("--raw")
+            .arg("-z")
+            .arg("-M")
+            .arg("--no-abbrev")
+            .arg("--pretty=format:%x1e%H")
+            .arg(range);
+        parse_raw_log(&cmd.stdout()?)
+    }
+
+    // whether both the index and the tracked files in the working tree are identical to
+    // `rev`, per `diff --quiet [--cached] <rev>`. untracked files aren't considered
+    pub fn matches_commit(&self, rev: &str) -> Result<bool, GitError> {
+        for cached in [true, false] {
+            let mut cmd = self.fresh();
+            cmd.diff().arg("--quiet").arg("--no-ext-diff");
+            if cached {
+                cmd.arg("--cached");
+            }
+            let output = cmd.arg(rev).allow_exit_codes(&[1]).output()?;
+            if !output.success() {
+                return Ok(false);
+            }
+        }
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_raw_log_records() {
+        let out = "\x1eaaaa\n:100644 100644 1111 2222 M\0src/a.rs\0\
+                   :000000 100644 0000 3333 A\0new.txt\0\
+                   :100644 100644 4444 4444 R100\0old.rs\0moved.rs\0\0\
+                   \x1ebbbb\0";
+        let commits = parse_raw_log(out).unwrap();
+        assert_eq!(commits.len(), 2);
+        assert_eq!(commits[0].hash, "aaaa");
+        assert_eq!(
+            commits[0].changes,
+            [
+                FileChange {
+                    status: ChangeKind::Modified,
+                    old_path: Some(String::from("src/a.rs")),
+                    new_path: Some(String::from("src/a.rs")),
+                    similarity: None,
+                },
+                FileChange {
+                    status: ChangeKind::Added,
+                    old_path: None,
+                    new_path: Some(String::from("new.txt")),
+                    similarity: None,
+                },
+                FileChange {
+                    status: ChangeKind::Renamed,
+                    old_path: Some(String::from("old.rs")),
+                    new_path: Some(String::from("moved.rs")),
+                    similarity: Some(100),
+                },
+            ]
+        );
+        // a commit without changes
+        assert_eq!(commits[1].hash, "bbbb");
+        assert!(commits[1].changes.is_empty());
+    }
+
+    #[test]
+    fn parse_raw_log_rejects_truncated_renames() {
+        assert!(parse_raw_log("\x1eaaaa\n:100644 100644 1111 2222 R090\0old.rs\0").is_err());
+    }
+
+    #[test]
+    fn parse_diff_keeps_carriage_returns() {
+        let out = "diff --git a/win.txt b/win.txt\n\
+                   --- a/win.txt\n\
+                   +++ b/win.txt\n\
+                   @@ -1,2 +1,2 @@\n\
+                   \x20keep\r\n\
+                   -old\r\n\
+                   +new\r\n";
+        let files = parse_diff(out).unwrap();
+        assert_eq!(files.len(), 1);
+        assert_eq!(files[0].old_path.as_deref(), Some("win.txt"));
+        assert_eq!(files[0].new_path.as_deref(), Some("win.txt"));
+        assert_eq!(
+            files[0].hunks[0].lines,
+            [
+                DiffLine::Context(String::from("keep\r")),
+                DiffLine::Removed(String::from("old\r")),
+                DiffLine::Added(String::from("new\r")),
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_diff_rejects_hunks_longer_than_their_header() {
+        let out = "diff --git a/new.txt b/new.txt\n\
+                   new file mode 100644\n\
+                   --- /dev/null\n\
+                   +++ b/new.txt\n\
+                   @@ -0,0 +1 @@\n\
+                   -stray\n";
+        assert!(matches!(parse_diff(out), Err(GitError::Parse(_))));
+    }
+}
@@ -0,0 +1,105 @@
+use std::fmt;
+use std::io;
+use std::string::FromUtf8Error;
+
+#[derive(Debug)]
+pub enum GitError {
+    // git could not be spawned at all
+    Io(io::Error),
+    // git ran but exited unsuccessfully
+    NonZeroExit {
+        command: String,
+        code: Option<i32>,
+        stderr: String,
+    },
+    // git printed something that isn't valid UTF-8
+    InvalidUtf8(FromUtf8Error),
+    // git's output didn't have the expected shape
+    Parse(String),
+    // the requested `stash@{N}` doesn't exist
+    NoStashEntry(String),
+    // the branch has no upstream configured
+    NoUpstream(String),
+    // the revision doesn't name an object in the repository
+    InvalidObject(String),
+    // the path doesn't lie inside the repository's working tree
+    PathOutsideRepo(String),
+    // the builder was used in a way git would reject
+    InvalidCommand(String),
+    // one step of a multi-command helper failed, `step` says which and what state it left
+    StepFailed {
+        step: String,
+        source: Box<GitError>,
+    },
+}
+
+// longest stderr excerpt `Display` includes, in characters
+const STDERR_EXCERPT: usize = 512;
+
+// stderr folded onto one line and cut to `STDERR_EXCERPT`, so errors stay loggable
+fn excerpt(stderr: &str) -> String {
+    let line = stderr
+        .lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty())
+        .collect::<Vec<&str>>()
+        .join(" | ");
+    match line.char_indices().nth(STDERR_EXCERPT) {
+        Some((at, _)) => format!("{}...", &line[..at]),
+        None => line,
+    }
+}
+
+impl fmt::Display for GitError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            GitError::Io(e) => write!(f, "failed to run git: {}", e),
+            GitError::NonZeroExit {
+                command,
+                code,
+                stderr,
+            } => {
+                match code {
+                    Some(code) => write!(f, "`{}` exited with status {}", command, code)?,
+                    None => write!(f, "`{}` was terminated by a signal", command)?,
+                }
+                let stderr = excerpt(stderr);
+                if !stderr.is_empty() {
+                    write!(f, ": {}", stderr)?;
+                }
+                Ok(())
+            }
+            GitError::InvalidUtf8(e) => write!(f, "git output was not valid UTF-8: {}", e),
+            GitError::Parse(msg) => write!(f, "could not parse git output: {}", msg),
+            GitError::NoStashEntry(selector) => write!(f, "no stash entry {}", selector),
+            GitError::NoUpstream(branch) => write!(f, "{} has no upstream branch", branch),
+            GitError::InvalidObject(rev) => write!(f, "{} is not a valid object", rev),
+            GitError::PathOutsideRepo(path) => write!(f, "{} is outside the repository", path),
+            GitError::InvalidCommand(msg) => write!(f, "invalid git command: {}", msg),
+            GitError::StepFailed { step, source } => write!(f, "{}: {}", step, source),
+        }
+    }
+}
+
+impl std::error::Error for GitError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            GitError::Io(e) => Some(e),
+            GitError::InvalidUtf8(e) => Some(e),
+            GitError::StepFailed { source, .. } => Some(source.as_ref()),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for GitError {
+    fn from(e: io::Error) -> GitError {
+        GitError::Io(e)
+    }
+}
+
+impl From<FromUtf8Error> for GitError {
+    fn from(e: FromUtf8Error) -> GitError {
+        GitError::InvalidUtf8(e)
+    }
+}
@@ -0,0 +1,86 @@
+use std::path::{Path, PathBuf};
+
+use crate::{GitCommand, GitError};
+
+// the hooks git itself runs, see githooks(5)
+const HOOK_NAMES: [&str; 28] = [
+    "applypatch-msg",
+    "pre-applypatch",
+    "post-applypatch",
+    "pre-commit",
+    "pre-merge-commit",
+    "prepare-commit-msg",
+    "commit-msg",
+    "post-commit",
+    "pre-rebase",
+    "post-checkout",
+    "post-merge",
+    "pre-push",
+    "pre-receive",
+    "update",
+    "proc-receive",
+    "post-receive",
+    "post-update",
+    "reference-transaction",
+    "push-to-checkout",
+    "pre-auto-gc",
+    "post-rewrite",
+    "sendemail-validate",
+    "fsmonitor-watchman",
+    "p4-changelist",
+    "p4-prepare-changelist",
+    "p4-post-changelist",
+    "p4-pre-submit",
+    "post-index-change",
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HookInfo {
+    pub name: String,
+    pub path: PathBuf,
+    // whether git would run it: installed under its own name and executable
+    pub enabled: bool,
+    pub executable: bool,
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .map(|m| m.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+// there's no executable bit to check, git runs whatever is there
+#[cfg(not(unix))]
+fn is_executable(path: &Path) -> bool {
+    path.is_file()
+}
+
+impl GitCommand {
+    // the known hooks present in the hooks directory (`core.hooksPath` if set), installed
+    // ones or their `.sample` templates, which git never runs. a missing directory has none
+    pub fn hooks(&self) -> Result<Vec<HookInfo>, GitError> {
+        let dir = self.git_path("hooks")?;
+        let mut hooks = Vec::new();
+        for name in HOOK_NAMES {
+            let installed = dir.join(name);
+            let sample = dir.join(format!("{}.sample", name));
+            let (path, is_sample) = if installed.is_file() {
+                (installed, false)
+            } else if sample.is_file() {
+                (sample, true)
+            } else {
+                continue;
+            };
+            let executable = is_executable(&path);
+            hooks.push(HookInfo {
+                name: String::from(name),
+                enabled: executable && !is_sample,
+                executable,
+                path,
+            });
+        }
+        Ok(hooks)
+    }
+}
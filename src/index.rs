@@ -0,0 +1,207 @@
+use std::path::{Path, PathBuf};
+
+use crate::{split_nul, GitCommand, GitError};
+
+fn nul_paths(cmd: &GitCommand) -> Result<Vec<PathBuf>, GitError> {
+    Ok(split_nul(&cmd.stdout()?)
+        .into_iter()
+        .map(PathBuf::from)
+        .collect())
+}
+
+impl GitCommand {
+    // `add -- <paths>`, the separator keeps paths like `-foo` from being read as options
+    pub fn add_paths(&mut self, paths: &[&str]) -> &mut GitCommand {
+        self.add().paths(paths)
+    }
+
+    // `add -A`
+    pub fn add_all(&mut self) -> &mut GitCommand {
+        self.add().arg("-A")
+    }
+
+    // `add -u`
+    pub fn add_update(&mut self) -> &mut GitCommand {
+        self.add().arg("-u")
+    }
+
+    // `rm [--cached] [-r] [-f] -- <paths>`, `cached` untracks without deleting the files
+    pub fn rm_paths(
+        &mut self,
+        paths: &[&str],
+        cached: bool,
+        recursive: bool,
+        force: bool,
+    ) -> &mut GitCommand {
+        self.rm();
+        if cached {
+            self.arg("--cached");
+        }
+        if recursive {
+            self.arg("-r");
+        }
+        if force {
+            self.arg("-f");
+        }
+        self.paths(paths)
+    }
+
+    // `mv [-f] <from> <to>`, returning the source and destination git will end up with.
+    // moving into an existing directory keeps the source's basename
+    pub fn mv_path(&mut self, from: &str, to: &str, force: bool) -> (PathBuf, PathBuf) {
+        self.mv();
+        if force {
+            self.arg("-f");
+        }
+        self.arg(from).arg(to);
+
+        let base = self.cwd.clone().unwrap_or_else(|| PathBuf::from("."));
+        let source = PathBuf::from(from);
+        let destination = match source.file_name() {
+            Some(name) if base.join(to).is_dir() => Path::new(to).join(name),
+            _ => PathBuf::from(to),
+        };
+        (source, destination)
+    }
+
+    // `restore --source=<source> [--staged] [--worktree] -- <paths>`: `staged` alone resets
+    // the index entries, `worktree` alone (git's default) overwrites the files, both do
+    // both. choosing neither makes the command fail validation
+    pub fn restore_tree(
+        &mut self,
+        source: &str,
+        staged: bool,
+        worktree: bool,
+        paths: &[&str],
+    ) -> &mut GitCommand {
+        self.restore().arg(&format!("--source={}", source));
+        if !staged && !worktree {
+            return self.invalidate(String::from(
+                "restore needs at least one of the index and the worktree",
+            ));
+        }
+        if staged {
+            self.arg("--staged");
+        }
+        if worktree {
+            self.arg("--worktree");
+        }
+        if !paths.is_empty() {
+            self.paths(paths);
+        }
+        self
+    }
+
+    // `reset --hard <rev>` and, with `remove_untracked`, `clean -fd`. this throws away every
+    // uncommitted change and, with `remove_untracked`, every untracked file and directory
+    // that isn't ignored, with no way to get them back. the error names the failed step
+    pub fn reset_hard_clean(&self, rev: &str, remove_untracked: bool) -> Result<(), GitError> {
+        self.fresh()
+            .reset()
+            .arg("--hard")
+            .arg(rev)
+            .output()
+            .map_err(|e| GitError::StepFailed {
+                step: format!("resetting to {}", rev),
+                source: Box::new(e),
+            })?;
+        if remove_untracked {
+            self.fresh()
+                .clean()
+                .arg("-fd")
+                .output()
+                .map_err(|e| GitError::StepFailed {
+                    step: format!(
+                        "removing untracked files (the reset to {} already happened)",
+                        rev
+                    ),
+                    source: Box::new(e),
+                })?;
+        }
+        Ok(())
+    }
+
+    // files staged in the index, `diff --cached --name-only -z`
+    pub fn staged_changes(&self) -> Result<Vec<PathBuf>, GitError> {
+        self.staged_changes_for(&[])
+    }
+
+    // `staged_changes` limited to `pathspecs`, which may use magic like `:(exclude)`
+    pub fn staged_changes_for(&self, pathspecs: &[&str]) -> Result<Vec<PathBuf>, GitError> {
+        let mut cmd = self.fresh_parser();
+        cmd.diff().arg("--cached").arg("--name-only").arg("-z");
+        if !pathspecs.is_empty() {
+            cmd.paths(pathspecs);
+        }
+        nul_paths(&cmd)
+    }
+
+    // tracked files with changes not yet staged, `diff --name-only -z`
+    pub fn unstaged_changes(&self) -> Result<Vec<PathBuf>, GitError> {
+        self.unstaged_changes_for(&[])
+    }
+
+    pub fn unstaged_changes_for(&self, pathspecs: &[&str]) -> Result<Vec<PathBuf>, GitError> {
+        let mut cmd = self.fresh_parser();
+        cmd.diff().arg("--name-only").arg("-z");
+        if !pathspecs.is_empty() {
+            cmd.paths(pathspecs);
+        }
+        nul_paths(&cmd)
+    }
+
+    // `ls-files --others --exclude-standard -z`
+    pub fn untracked_files(&self) -> Result<Vec<PathBuf>, GitError> {
+        nul_paths(
+            self.fresh_parser()
+                .ls_files()
+                .arg("--others")
+                .arg("--exclude-standard")
+                .arg("-z"),
+        )
+    }
+
+    // `ls-files --error-unmatch -- <path>`, which exits 1 when the path isn't tracked
+    pub fn is_tracked(&self, path: &str) -> Result<bool, GitError> {
+        let mut cmd = self.fresh();
+        cmd.ls_files().arg("--error-unmatch").paths(&[path]);
+        let output = cmd.execute()?;
+        match output.code {
+            Some(0) => Ok(true),
+            Some(1) => Ok(false),
+            _ => Err(cmd.failure(&output)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn add_paths_separates_dash_paths() {
+        let mut cmd = GitCommand::create(false);
+        assert_eq!(
+            cmd.add_paths(&["-foo"]).dry_run(),
+            ["git", "add", "--", "-foo"]
+        );
+    }
+
+    #[test]
+    fn rm_paths_cached_only() {
+        let mut cmd = GitCommand::create(false);
+        assert_eq!(
+            cmd.rm_paths(&["a.txt"], true, false, false).dry_run(),
+            ["git", "rm", "--cached", "--", "a.txt"]
+        );
+    }
+
+    #[test]
+    fn rm_paths_force_recursive() {
+        let mut cmd = GitCommand::create(false);
+        assert_eq!(
+            cmd.rm_paths(&["dir"], false, true, true).dry_run(),
+            ["git", "rm", "-r", "-f", "--", "dir"]
+        );
+    }
+}
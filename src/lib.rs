@@ -1,6 +1,57 @@
-use std::process::Command;
+use std::fmt;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
 use std::vec::Vec;
 
+mod attr;
+mod blame;
+mod branch;
+mod commit;
+mod config;
+mod diff;
+mod error;
+mod hooks;
+mod index;
+mod log;
+mod merge;
+mod object;
+mod pipeline;
+mod pretty;
+mod reflog;
+mod refs;
+mod remote;
+mod repo;
+mod shell;
+mod stash;
+mod status;
+mod submodule;
+mod tag;
+mod worktree;
+
+pub use attr::AttrValue;
+pub use blame::{BlameLine, BlameOpts};
+pub use branch::BranchTracking;
+pub use commit::SignatureStatus;
+pub use diff::{ChangeKind, CommitChanges, DiffLine, DiffOpts, FileChange, FileDiff, Hunk};
+pub use error::GitError;
+pub use hooks::HookInfo;
+pub use log::{AuthorChurn, CommitInfo, CommitIter, LogOrder};
+pub use object::ObjectKind;
+pub use pipeline::GitPipeline;
+pub use pretty::{PrettyField, PrettyFormat};
+pub use remote::{CloneOpts, PullMode, GITHUB_PR_REFSPEC, GITLAB_MR_REFSPEC};
+pub use repo::{GitOperation, RepoHealth, RepoInfo};
+pub use stash::StashEntryFull;
+pub use status::{StatusEntry, StatusV2};
+pub use submodule::SubmoduleInfo;
+pub use tag::TagInfo;
+
+#[cfg(feature = "serde")]
+mod serialize;
+#[cfg(feature = "serde")]
+pub use serialize::SerializableCommand;
+
 /*
  * macro for generating git commands
  */
@@ -36,14 +87,33 @@ macro_rules! add_extra_git_text {
     };
 }
 
+// splits `-z` output into its records, dropping the trailing terminator
+pub(crate) fn split_nul(out: &str) -> Vec<String> {
+    out.split('\0')
+        .filter(|r| !r.is_empty())
+        .map(String::from)
+        .collect()
+}
+
 pub fn find_repo_path() -> String {
     GitCommand::create(false)
         .rev_parse()
         .options()
         .double(String::from("show-toplevel"), None, None)
         .done()
-        .run(false)
-        .unwrap_or(String::from(""))
+        .run_trimmed()
+        .unwrap_or_default()
+}
+
+// the repository's git directory, `rev-parse --absolute-git-dir` so it's already resolved
+// against the current directory (plain `--git-dir` is relative from the toplevel)
+pub fn find_git_dir() -> Result<PathBuf, GitError> {
+    Ok(PathBuf::from(
+        GitCommand::create(false)
+            .rev_parse()
+            .arg("--absolute-git-dir")
+            .run_trimmed()?,
+    ))
 }
 
 pub fn find_repo_name() -> String {
@@ -52,23 +122,55 @@ pub fn find_repo_name() -> String {
     String::from(res[res.len() - 1])
 }
 
+// whether `name` is acceptable as a branch name, per `git check-ref-format --branch`
+pub fn is_valid_ref_name(name: &str) -> bool {
+    normalize_ref_name(name).is_some()
+}
+
+// the branch name git would actually use for `name`, or `None` if it's invalid
+pub fn normalize_ref_name(name: &str) -> Option<String> {
+    let output = GitCommand::create(false)
+        .check_ref_format()
+        .arg("--branch")
+        .arg(name)
+        .execute()
+        .ok()?;
+    if !output.success() {
+        return None;
+    }
+    let normalized = String::from_utf8(output.stdout).ok()?;
+    Some(trim_output(normalized, Trim::Newline))
+}
+
 pub struct GitOptions<'a> {
     parent: &'a mut GitCommand,
-    single_dash: String,
+    single_dash: Vec<String>,
     double_dash: Vec<(String, String, bool)>,
+    // whether `done` places the options before the subcommand
+    prepend: bool,
 }
 
 impl<'a> GitOptions<'a> {
-    fn new(parent: &'a mut GitCommand) -> GitOptions<'a> {
+    fn new(parent: &'a mut GitCommand, prepend: bool) -> GitOptions<'a> {
         GitOptions {
             parent,
-            single_dash: String::from(""),
+            single_dash: Vec::new(),
             double_dash: Vec::new(),
+            prepend,
         }
     }
 
-    pub fn single(&mut self, c: char) -> &'a mut GitOptions {
-        self.single_dash.push(c);
+    // one short flag, `-c`. repeated calls give separate tokens
+    pub fn single(&mut self, c: char) -> &mut GitOptions<'a> {
+        self.single_dash.push(format!("-{}", c));
+        self
+    }
+
+    // several short flags clustered into one token, `-abc`. only boolean flags cluster
+    // safely (e.g. `clean -fdx`, `commit -av`); a flag taking a value such as `-n`, `-m`
+    // or `-U` would swallow the rest of the cluster as its value
+    pub fn flags(&mut self, chars: &str) -> &mut GitOptions<'a> {
+        self.single_dash.push(format!("-{}", chars));
         self
     }
 
@@ -77,7 +179,7 @@ impl<'a> GitOptions<'a> {
         name: String,
         value: Option<String>,
         equals: Option<bool>,
-    ) -> &'a mut GitOptions {
+    ) -> &mut GitOptions<'a> {
         self.double_dash.push((
             name,
             value.unwrap_or(String::from("")).clone(),
@@ -87,45 +189,113 @@ impl<'a> GitOptions<'a> {
     }
 
     fn __options(&self) -> Vec<String> {
-        let mut ret = Vec::new();
-        if self.single_dash.len() != 0 {
-            ret.push(String::from("-") + self.single_dash.as_str());
-        }
+        let mut ret = self.single_dash.clone();
         for (k, v, equals) in &self.double_dash {
-            ret.push(String::from("--") + k);
-            if !v.is_empty() {
-                ret.push(
-                    if equals.to_owned() {
-                        String::from("=")
-                    } else {
-                        String::from("")
-                    } + v.as_str(),
-                );
+            if v.is_empty() {
+                ret.push(String::from("--") + k);
+            } else if *equals {
+                ret.push(String::from("--") + k + "=" + v);
+            } else {
+                ret.push(String::from("--") + k);
+                ret.push(v.clone());
             }
         }
         ret
     }
 
     pub fn done(&mut self) -> &mut GitCommand {
-        self.parent.git_cmd.extend(self.__options());
-        &mut self.parent
+        let options = self.__options();
+        if self.prepend {
+            self.parent.push_global(options);
+        } else {
+            self.parent.git_cmd.extend(options);
+        }
+        self.parent
+    }
+}
+
+// how much of the end of stdout `run_trimmed_with` removes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trim {
+    // a single trailing `\n` (or `\r\n`)
+    Newline,
+    // all trailing whitespace
+    Whitespace,
+}
+
+pub(crate) fn trim_output(mut out: String, trim: Trim) -> String {
+    match trim {
+        Trim::Newline => {
+            if out.ends_with('\n') {
+                out.pop();
+                if out.ends_with('\r') {
+                    out.pop();
+                }
+            }
+        }
+        Trim::Whitespace => out.truncate(out.trim_end().len()),
     }
+    out
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GitOutput {
+    // `None` when git was killed by a signal
+    pub code: Option<i32>,
+    pub stdout: Vec<u8>,
+    pub stderr: Vec<u8>,
 }
 
+impl GitOutput {
+    pub fn success(&self) -> bool {
+        self.code == Some(0)
+    }
+}
+
+#[derive(Debug)]
 pub struct GitCommand {
     repo_name: Option<String>,
     find_root: bool,
     git_cmd: Vec<String>,
     git_cmd_started: bool,
+    // number of global options at the head of `git_cmd`
+    global_len: usize,
+    paths_started: bool,
+    git_binary: String,
+    cwd: Option<PathBuf>,
+    env: Vec<(String, String)>,
+    // environment belonging to the current command alone, e.g. a forced committer date,
+    // dropped by `then` and not passed on to helper commands
+    command_env: Vec<(String, String)>,
+    verbose: bool,
+    // set when a builder method was misused, reported by `validate`
+    invalid: Option<String>,
+    // non-zero exit codes `output` accepts as success
+    allowed_codes: Vec<i32>,
+    // fed to git's standard input
+    stdin: Option<Vec<u8>>,
+    // set by `null_terminated`, makes `run_records` split on NUL
+    null_terminated: bool,
 }
 
-impl<'a> GitCommand {
+impl GitCommand {
     pub fn create(find_root: bool) -> GitCommand {
         let mut git = GitCommand {
             repo_name: None,
             find_root,
             git_cmd_started: false,
             git_cmd: Vec::new(),
+            global_len: 0,
+            paths_started: false,
+            git_binary: String::from("git"),
+            cwd: None,
+            env: Vec::new(),
+            command_env: Vec::new(),
+            verbose: false,
+            invalid: None,
+            allowed_codes: Vec::new(),
+            stdin: None,
+            null_terminated: false,
         };
         if git.find_root {
             git.repo_name = Some(find_repo_name());
@@ -133,6 +303,124 @@ impl<'a> GitCommand {
         git
     }
 
+    // the name substituted for `%%repo_name%%`, discovered by `create(true)`
+    pub fn repo_name(&self) -> Option<&str> {
+        self.repo_name.as_deref()
+    }
+
+    // supplies the `%%repo_name%%` substitution directly, e.g. for bare repositories
+    pub fn set_repo_name(&mut self, name: &str) -> &mut GitCommand {
+        self.repo_name = Some(String::from(name));
+        self
+    }
+
+    // the git executable to invoke, defaults to `git` on the PATH
+    pub fn git_binary(&mut self, binary: &str) -> &mut GitCommand {
+        self.git_binary = String::from(binary);
+        self
+    }
+
+    // data written to the command's standard input, e.g. for `hash-object --stdin`
+    pub fn stdin(&mut self, data: &[u8]) -> &mut GitCommand {
+        self.stdin = Some(data.to_vec());
+        self
+    }
+
+    // working directory the command is run from
+    pub fn cwd<P: AsRef<Path>>(&mut self, dir: P) -> &mut GitCommand {
+        self.cwd = Some(dir.as_ref().to_path_buf());
+        self
+    }
+
+    // extra environment variable set for the command
+    pub fn env(&mut self, key: &str, value: &str) -> &mut GitCommand {
+        self.env.push((String::from(key), String::from(value)));
+        self
+    }
+
+    // an environment variable for the current command only, which `then` drops again
+    pub(crate) fn command_env(&mut self, key: &str, value: &str) -> &mut GitCommand {
+        self.command_env
+            .push((String::from(key), String::from(value)));
+        self
+    }
+
+    // everything exported to git, the builder's env followed by the current command's
+    pub(crate) fn all_env(&self) -> impl Iterator<Item = &(String, String)> {
+        self.env.iter().chain(&self.command_env)
+    }
+
+    // a new builder sharing this one's configuration and global options, but no subcommand
+    fn fresh(&self) -> GitCommand {
+        GitCommand {
+            repo_name: self.repo_name.clone(),
+            find_root: self.find_root,
+            git_cmd: self.git_cmd[..self.global_len].to_vec(),
+            git_cmd_started: false,
+            global_len: self.global_len,
+            paths_started: false,
+            git_binary: self.git_binary.clone(),
+            cwd: self.cwd.clone(),
+            env: self.env.clone(),
+            command_env: Vec::new(),
+            verbose: self.verbose,
+            invalid: None,
+            allowed_codes: Vec::new(),
+            stdin: None,
+            null_terminated: false,
+        }
+    }
+
+    // an exact copy, subcommand and all. not `Clone`, whose `clone` would shadow the
+    // `clone` subcommand on owned builders
+    pub(crate) fn duplicate(&self) -> GitCommand {
+        GitCommand {
+            repo_name: self.repo_name.clone(),
+            find_root: self.find_root,
+            git_cmd: self.git_cmd.clone(),
+            git_cmd_started: self.git_cmd_started,
+            global_len: self.global_len,
+            paths_started: self.paths_started,
+            git_binary: self.git_binary.clone(),
+            cwd: self.cwd.clone(),
+            env: self.env.clone(),
+            command_env: self.command_env.clone(),
+            verbose: self.verbose,
+            invalid: self.invalid.clone(),
+            allowed_codes: self.allowed_codes.clone(),
+            stdin: self.stdin.clone(),
+            null_terminated: self.null_terminated,
+        }
+    }
+
+    // global options go after any earlier ones but before the subcommand
+    fn push_global(&mut self, options: Vec<String>) {
+        let at = self.global_len;
+        self.global_len += options.len();
+        self.git_cmd.splice(at..at, options);
+    }
+
+    // `git -c <key>=<value> ...`, overriding a config value for this command only
+    pub fn global_config(&mut self, key: &str, value: &str) -> &mut GitCommand {
+        self.push_global(vec![String::from("-c"), format!("{}={}", key, value)]);
+        self
+    }
+
+    // `fresh`, with output settings the parsers rely on: UTF-8 log messages and
+    // paths that aren't octal-escaped
+    fn fresh_parser(&self) -> GitCommand {
+        let mut cmd = self.fresh();
+        cmd.global_config("i18n.logOutputEncoding", "UTF-8")
+            .global_config("core.quotePath", "false");
+        cmd
+    }
+
+    // echo every command to stderr right before it is spawned
+    pub fn verbose(&mut self, on: bool) -> &mut GitCommand {
+        self.verbose = on;
+        self
+    }
+
     fn sanitize(&self, a: String) -> String {
         if self.repo_name.is_some() {
             a.replace("%%repo_name%%", self.repo_name.clone().unwrap().as_str())
@@ -141,38 +429,193 @@ impl<'a> GitCommand {
         }
     }
 
+    // drops the subcommand and its arguments, keeping configuration and global options
     fn _reset(&mut self) {
-        self.git_cmd = vec![String::from("git")];
+        self.git_cmd.truncate(self.global_len);
         self.git_cmd_started = false;
+        self.paths_started = false;
+        self.invalid = None;
+        self.allowed_codes.clear();
+        self.stdin = None;
+        self.null_terminated = false;
+        self.command_env.clear();
+    }
+
+    // starts a new command on the same builder, keeping its cwd, env and global options:
+    // `cmd.status().run(false)?; cmd.then().log().run(false)?;`
+    pub fn then(&mut self) -> &mut GitCommand {
+        self._reset();
+        self
+    }
+
+    // the arguments passed to git, without the binary
+    pub fn command_list(&self) -> &[String] {
+        &self.git_cmd
+    }
+
+    pub fn arg_count(&self) -> usize {
+        self.git_cmd.len()
     }
 
-    fn command_list(&self) -> Vec<String> {
-        self.git_cmd.clone()
+    pub fn is_empty(&self) -> bool {
+        self.git_cmd.is_empty()
+    }
+
+    // checks the builder is runnable, `run` and the other executors call this first
+    pub fn validate(&self) -> Result<(), GitError> {
+        if let Some(msg) = &self.invalid {
+            return Err(GitError::InvalidCommand(msg.clone()));
+        }
+        if self.is_empty() {
+            return Err(GitError::InvalidCommand(String::from(
+                "no git subcommand or arguments were given",
+            )));
+        }
+        Ok(())
     }
 
     fn command(&self) -> String {
-        let added = String::from("git ") + self.git_cmd.join(" ").as_str();
-        String::from(added)
+        std::iter::once(&self.git_binary)
+            .chain(&self.git_cmd)
+            .map(|arg| shell::shell_quote(arg))
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+
+    fn process(&self) -> Command {
+        let mut process = Command::new(&self.git_binary);
+        process.args(self.command_list());
+        if let Some(dir) = &self.cwd {
+            process.current_dir(dir);
+        }
+        process.envs(self.all_env().map(|(k, v)| (k, v)));
+        if self.verbose {
+            eprintln!("+ {}", self.shell_line());
+        }
+        process
     }
 
-    pub fn run(&self, debug: bool) -> Option<String> {
+    pub fn run(&self, debug: bool) -> Result<Option<String>, GitError> {
         if debug {
             println!("{}", self.command());
-            return None;
+            return Ok(None);
         }
-        let output = Command::new("git")
-            .args(self.command_list())
-            .output()
-            .expect("Failed to run git command");
-        println!(
-            "{:?}",
-            Command::new("git").args(self.command_list()).get_args()
-        );
-        Some(String::from_utf8(output.stdout).unwrap())
+        Ok(Some(String::from_utf8(self.run_bytes()?)?))
     }
 
-    pub fn options(&mut self) -> GitOptions {
-        GitOptions::new(self)
+    // stdout without its trailing newline, for commands that print a single value
+    pub fn run_trimmed(&self) -> Result<String, GitError> {
+        self.run_trimmed_with(Trim::Newline)
+    }
+
+    pub fn run_trimmed_with(&self, trim: Trim) -> Result<String, GitError> {
+        Ok(trim_output(self.stdout()?, trim))
+    }
+
+    // stdout exactly as git wrote it
+    pub fn run_bytes(&self) -> Result<Vec<u8>, GitError> {
+        Ok(self.output()?.stdout)
+    }
+
+    // stdout split into records: on NUL after `null_terminated`, otherwise into lines
+    pub fn run_records(&self) -> Result<Vec<String>, GitError> {
+        let out = self.stdout()?;
+        if self.null_terminated {
+            return Ok(split_nul(&out));
+        }
+        Ok(out.lines().map(String::from).collect())
+    }
+
+    // stdout with invalid UTF-8 replaced by U+FFFD, for display rather than parsing
+    pub fn run_lossy(&self) -> Result<String, GitError> {
+        Ok(String::from_utf8_lossy(&self.run_bytes()?).into_owned())
+    }
+
+    // runs the command and captures its output, whatever the exit status
+    fn execute(&self) -> Result<GitOutput, GitError> {
+        self.validate()?;
+        let output = match &self.stdin {
+            None => self.process().output()?,
+            Some(data) => {
+                let mut child = self
+                    .process()
+                    .stdin(Stdio::piped())
+                    .stdout(Stdio::piped())
+                    .stderr(Stdio::piped())
+                    .spawn()?;
+                let pipe = child.stdin.take();
+                // written from another thread so git can't stall on a full stdout pipe while
+                // we're blocked on its stdin; a write error shows up in git's exit status
+                std::thread::scope(|scope| {
+                    scope.spawn(move || {
+                        if let Some(mut pipe) = pipe {
+                            let _ = pipe.write_all(data);
+                        }
+                    });
+                    child.wait_with_output()
+                })?
+            }
+        };
+        Ok(GitOutput {
+            code: output.status.code(),
+            stdout: output.stdout,
+            stderr: output.stderr,
+        })
+    }
+
+    // starts the command with piped stdout and stderr for callers that read as it runs
+    fn spawn(&self) -> Result<Child, GitError> {
+        self.validate()?;
+        Ok(self
+            .process()
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?)
+    }
+
+    // exit codes that report information rather than failure, e.g. 1 from `diff --quiet`.
+    // `run` and friends return `Ok` for them, `output` still exposes the actual code
+    pub fn allow_exit_codes(&mut self, codes: &[i32]) -> &mut GitCommand {
+        self.allowed_codes.extend_from_slice(codes);
+        self
+    }
+
+    // runs the command, treating a non-zero exit status as an error unless it was allowed
+    pub fn output(&self) -> Result<GitOutput, GitError> {
+        let output = self.execute()?;
+        let allowed = output.code.is_some_and(|c| self.allowed_codes.contains(&c));
+        if !output.success() && !allowed {
+            return Err(self.failure(&output));
+        }
+        Ok(output)
+    }
+
+    fn failure(&self, output: &GitOutput) -> GitError {
+        GitError::NonZeroExit {
+            command: self.command(),
+            code: output.code,
+            stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+        }
+    }
+
+    fn stdout(&self) -> Result<String, GitError> {
+        Ok(String::from_utf8(self.output()?.stdout)?)
+    }
+
+    // the full argument vector that `run` would execute, without running or printing it
+    pub fn dry_run(&self) -> Vec<String> {
+        let mut cmd = vec![self.git_binary.clone()];
+        cmd.extend(self.git_cmd.iter().cloned());
+        cmd
+    }
+
+    pub fn options(&mut self) -> GitOptions<'_> {
+        GitOptions::new(self, false)
+    }
+
+    // options for git itself rather than the subcommand, e.g. `git --no-optional-locks status`
+    pub fn pre_options(&mut self) -> GitOptions<'_> {
+        GitOptions::new(self, true)
     }
 
     // main commands
@@ -180,10 +623,20 @@ impl<'a> GitCommand {
     add_git_command!(status, "status");
     // reset
     add_git_command!(reset, "reset");
+    // clean
+    add_git_command!(clean, "clean");
     // add
     add_git_command!(add, "add");
     // revParse
     add_git_command!(rev_parse, "rev-parse");
+    // rev-list
+    add_git_command!(rev_list, "rev-list");
+    // reflog
+    add_git_command!(reflog, "reflog");
+    // ls-tree
+    add_git_command!(ls_tree, "ls-tree");
+    // for-each-ref
+    add_git_command!(for_each_ref, "for-each-ref");
     // init
     add_git_command!(init, "init");
     // log
@@ -192,6 +645,10 @@ impl<'a> GitCommand {
     add_git_command!(checkout, "checkout");
     // branch
     add_git_command!(branch, "branch");
+    // cat-file
+    add_git_command!(cat_file, "cat-file");
+    // check-ref-format
+    add_git_command!(check_ref_format, "check-ref-format");
     // clone
     add_git_command!(clone, "clone");
     // commit
@@ -200,14 +657,22 @@ impl<'a> GitCommand {
     add_git_command!(config, "config");
     // submodule
     add_git_command!(submodule, "submodule");
+    // diff
+    add_git_command!(diff, "diff");
     // fetch
     add_git_command!(fetch, "fetch");
+    // ls-files
+    add_git_command!(ls_files, "ls-files");
     // merge
     add_git_command!(merge, "merge");
+    // merge-base
+    add_git_command!(merge_base, "merge-base");
     // mv
     add_git_command!(mv, "mv");
     // pull
     add_git_command!(pull, "pull");
+    // push
+    add_git_command!(push, "push");
     // pull_origin
     add_git_command!(pull_origin, "pull_origin");
     // push_origin
@@ -222,6 +687,8 @@ impl<'a> GitCommand {
     add_git_command!(restore, "restore");
     // show
     add_git_command!(show, "show");
+    // stash
+    add_git_command!(stash, "stash");
     // switch
     add_git_command!(switch, "switch");
     // tag
@@ -233,6 +700,109 @@ impl<'a> GitCommand {
     // upstream
     add_git_command!(upstream, "upstream", false);
 
+    // the subcommand chosen so far, e.g. `commit`
+    fn current_subcommand(&self) -> Option<&str> {
+        if !self.git_cmd_started {
+            return None;
+        }
+        self.git_cmd.get(self.global_len).map(String::as_str)
+    }
+
+    // records a misuse for `validate` to report, keeping the first one
+    fn invalidate(&mut self, msg: String) -> &mut GitCommand {
+        if self.invalid.is_none() {
+            self.invalid = Some(msg);
+        }
+        self
+    }
+
+    // suppresses progress and status chatter on commands that support it and is a no-op
+    // elsewhere. `diff --quiet` is left alone since it changes the exit status instead
+    pub fn quiet(&mut self) -> &mut GitCommand {
+        let at = match self.current_subcommand() {
+            // `git submodule --quiet update`, the flag belongs before the sub-subcommand
+            Some("submodule") => self.global_len + 1,
+            Some(
+                "clone" | "fetch" | "pull" | "push" | "checkout" | "switch" | "restore" | "commit"
+                | "merge" | "rebase" | "reset" | "stash" | "init" | "rm" | "clean" | "branch"
+                | "worktree" | "gc",
+            ) => self
+                .git_cmd
+                .iter()
+                .position(|a| self.paths_started && a == "--")
+                .unwrap_or(self.git_cmd.len()),
+            _ => return self,
+        };
+        self.git_cmd.insert(at, String::from("--quiet"));
+        self
+    }
+
+    // `-z` on subcommands that support it, so paths containing newlines survive and
+    // `run_records` splits on NUL. anything else fails validation
+    pub fn null_terminated(&mut self) -> &mut GitCommand {
+        match self.current_subcommand() {
+            Some(
+                "status" | "diff" | "diff-tree" | "diff-index" | "ls-files" | "ls-tree" | "log"
+                | "show" | "grep" | "config" | "check-attr" | "check-ignore",
+            ) => {}
+            other => {
+                let msg = format!("-z isn't supported by {}", other.unwrap_or("git"));
+                return self.invalidate(msg);
+            }
+        }
+        let at = self
+            .git_cmd
+            .iter()
+            .position(|a| self.paths_started && a == "--")
+            .unwrap_or(self.git_cmd.len());
+        self.git_cmd.insert(at, String::from("-z"));
+        self.null_terminated = true;
+        self
+    }
+
+    // a user-defined alias as the subcommand, e.g. `alias("lg").arg("--all")`. git expands
+    // it itself, including `!`-prefixed shell aliases, whose output is captured as usual
+    pub fn alias(&mut self, name: &str) -> &mut GitCommand {
+        self.subcommand(name)
+    }
+
+    // for subcommands whose builder name is taken by a typed helper of the same name
+    fn subcommand(&mut self, name: &str) -> &mut GitCommand {
+        if !self.git_cmd_started {
+            self.git_cmd_started = true;
+            self.git_cmd.push(String::from(name));
+        }
+        self
+    }
+
+    // argument appended verbatim, without placeholder substitution
+    pub fn arg(&mut self, arg: &str) -> &mut GitCommand {
+        self.git_cmd.push(String::from(arg));
+        self
+    }
+
+    // `-- <paths>`, the separator is only emitted once however often this is called
+    pub fn paths(&mut self, paths: &[&str]) -> &mut GitCommand {
+        if !self.paths_started {
+            self.paths_started = true;
+            self.git_cmd.push(String::from("--"));
+        }
+        self.git_cmd.extend(paths.iter().map(|p| String::from(*p)));
+        self
+    }
+
+    // `<from>..<to>` as a single token
+    pub fn range(&mut self, from: &str, to: &str) -> &mut GitCommand {
+        self.git_cmd.push(format!("{}..{}", from, to));
+        self
+    }
+
+    // `<a>...<b>`, the symmetric difference of the two revisions
+    pub fn range_symmetric(&mut self, a: &str, b: &str) -> &mut GitCommand {
+        self.git_cmd.push(format!("{}...{}", a, b));
+        self
+    }
+
     // text appended to the git command
     // branch_name
     add_extra_git_text!(branch_name, GitCommand::sanitize);
@@ -241,3 +811,63 @@ impl<'a> GitCommand {
     // text
     add_extra_git_text!(text, GitCommand::sanitize);
 }
+
+// the shell-quoted command line, e.g. `git commit -m 'first commit'`
+impl fmt::Display for GitCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.command())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn range_is_a_single_token() {
+        let mut cmd = GitCommand::create(false);
+        assert_eq!(
+            cmd.log().range("main", "feature").dry_run(),
+            ["git", "log", "main..feature"]
+        );
+    }
+
+    #[test]
+    fn range_symmetric_is_a_single_token() {
+        let mut cmd = GitCommand::create(false);
+        assert_eq!(
+            cmd.log().range_symmetric("main", "feature").dry_run(),
+            ["git", "log", "main...feature"]
+        );
+    }
+
+    #[test]
+    fn alias_is_the_subcommand() {
+        let mut cmd = GitCommand::create(false);
+        cmd.alias("lg").arg("--all");
+        assert_eq!(cmd.dry_run(), ["git", "lg", "--all"]);
+        // only the first subcommand counts
+        cmd.alias("other");
+        assert_eq!(cmd.dry_run(), ["git", "lg", "--all"]);
+    }
+
+    #[test]
+    fn clone_is_the_subcommand_on_owned_builders() {
+        assert_eq!(
+            GitCommand::create(false)
+                .clone()
+                .url("https://h/x.git")
+                .dry_run(),
+            ["git", "clone", "https://h/x.git"]
+        );
+    }
+
+    #[test]
+    fn discovered_repo_name_is_trimmed() {
+        // the tests run from inside the crate's checkout
+        let cmd = GitCommand::create(true);
+        let name = cmd.repo_name().unwrap();
+        assert!(!name.is_empty());
+        assert_eq!(name, name.trim());
+    }
+}
@@ -0,0 +1,388 @@
+use std::io::{BufRead, BufReader, Read};
+use std::process::{Child, ChildStdout};
+
+use crate::pretty::{PrettyField, PrettyFormat};
+use crate::{GitCommand, GitError};
+
+// the fields `CommitInfo` is parsed from, in order, with the author optionally mailmapped
+fn commit_format(use_mailmap: bool) -> PrettyFormat {
+    let (name, email) = if use_mailmap {
+        (
+            PrettyField::MailmapAuthorName,
+            PrettyField::MailmapAuthorEmail,
+        )
+    } else {
+        (PrettyField::AuthorName, PrettyField::AuthorEmail)
+    };
+    PrettyFormat::new()
+        .field(PrettyField::Hash)
+        .field(name)
+        .field(email)
+        .field(PrettyField::AuthorDate)
+        .field(PrettyField::Subject)
+}
+
+/* how the log helpers order commits. `Reverse` is independent of the others, so
+`&[LogOrder::Topo, LogOrder::Reverse]` walks topologically, oldest first. note that git
+applies a limit before reversing */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogOrder {
+    // reverse chronological by commit date, git's own default
+    Default,
+    // `--date-order`
+    Date,
+    // `--author-date-order`
+    AuthorDate,
+    // `--topo-order`
+    Topo,
+    // `--reverse`
+    Reverse,
+}
+
+impl LogOrder {
+    fn flag(self) -> Option<&'static str> {
+        match self {
+            LogOrder::Default => None,
+            LogOrder::Date => Some("--date-order"),
+            LogOrder::AuthorDate => Some("--author-date-order"),
+            LogOrder::Topo => Some("--topo-order"),
+            LogOrder::Reverse => Some("--reverse"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitInfo {
+    pub hash: String,
+    pub author_name: String,
+    pub author_email: String,
+    // author date, seconds since the epoch
+    pub timestamp: i64,
+    pub subject: String,
+}
+
+pub(crate) fn parse_commit(record: &str) -> Result<CommitInfo, GitError> {
+    let [hash, author_name, author_email, timestamp, subject]: [String; 5] =
+        commit_format(false) // either way the record has the same shape
+            .split_record(record)?
+            .try_into()
+            .map_err(|_| GitError::Parse(format!("invalid commit record `{}`", record)))?;
+    Ok(CommitInfo {
+        timestamp: timestamp
+            .parse()
+            .map_err(|_| GitError::Parse(format!("invalid timestamp `{}`", timestamp)))?,
+        hash,
+        author_name,
+        author_email,
+        subject,
+    })
+}
+
+pub(crate) fn parse_commits(out: &str) -> Result<Vec<CommitInfo>, GitError> {
+    out.split_terminator('\x1e').map(parse_commit).collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AuthorChurn {
+    pub author: String,
+    pub insertions: u64,
+    pub deletions: u64,
+}
+
+// parses `log --numstat --pretty=format:%x1e%an` (or `%aN`), author lines being marked by 0x1e
+pub(crate) fn parse_churn(out: &str) -> Result<Vec<AuthorChurn>, GitError> {
+    let mut churn: Vec<AuthorChurn> = Vec::new();
+    let mut current = None;
+    for line in out.lines() {
+        if let Some(author) = line.strip_prefix('\x1e') {
+            current = Some(match churn.iter().position(|c| c.author == author) {
+                Some(i) => i,
+                None => {
+                    churn.push(AuthorChurn {
+                        author: String::from(author),
+                        insertions: 0,
+                        deletions: 0,
+                    });
+                    churn.len() - 1
+                }
+            });
+            continue;
+        }
+        if line.is_empty() {
+            continue;
+        }
+        let invalid = || GitError::Parse(format!("invalid numstat line `{}`", line));
+        let mut fields = line.splitn(3, '\t');
+        let (insertions, deletions) = match (fields.next(), fields.next()) {
+            (Some(i), Some(d)) => (i, d),
+            _ => return Err(invalid()),
+        };
+        // binary files have no line counts
+        if insertions == "-" || deletions == "-" {
+            continue;
+        }
+        let entry = &mut churn[current.ok_or_else(invalid)?];
+        entry.insertions += insertions.parse::<u64>().map_err(|_| invalid())?;
+        entry.deletions += deletions.parse::<u64>().map_err(|_| invalid())?;
+    }
+    Ok(churn)
+}
+
+// commits parsed one at a time as `git log` writes them
+pub struct CommitIter {
+    command: String,
+    child: Option<(Child, BufReader<ChildStdout>)>,
+    error: Option<GitError>,
+}
+
+impl CommitIter {
+    fn new(cmd: &GitCommand) -> CommitIter {
+        let mut iter = CommitIter {
+            command: cmd.command(),
+            child: None,
+            error: None,
+        };
+        match cmd.spawn() {
+            Ok(mut child) => {
+                let stdout = child.stdout.take().expect("stdout is piped");
+                iter.child = Some((child, BufReader::new(stdout)));
+            }
+            Err(e) => iter.error = Some(e),
+        }
+        iter
+    }
+
+    // reaps git once its output is exhausted, surfacing a failed exit
+    fn finish(&mut self) -> Option<GitError> {
+        let (mut child, _) = self.child.take()?;
+        let mut stderr = String::new();
+        if let Some(mut pipe) = child.stderr.take() {
+            let _ = pipe.read_to_string(&mut stderr);
+        }
+        match child.wait() {
+            Ok(status) if status.success() => None,
+            Ok(status) => Some(GitError::NonZeroExit {
+                command: self.command.clone(),
+                code: status.code(),
+                stderr,
+            }),
+            Err(e) => Some(GitError::Io(e)),
+        }
+    }
+}
+
+impl Iterator for CommitIter {
+    type Item = Result<CommitInfo, GitError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if let Some(e) = self.error.take() {
+            return Some(Err(e));
+        }
+        loop {
+            let (_, reader) = self.child.as_mut()?;
+            let mut buf = Vec::new();
+            match reader.read_until(b'\x1e', &mut buf) {
+                Ok(0) => return self.finish().map(Err),
+                Ok(_) => {
+                    let record = match String::from_utf8(buf) {
+                        Ok(record) => record,
+                        Err(e) => return Some(Err(e.into())),
+                    };
+                    let record = record.trim_end_matches('\x1e');
+                    if !record.is_empty() {
+                        return Some(parse_commit(record));
+                    }
+                }
+                Err(e) => return Some(Err(e.into())),
+            }
+        }
+    }
+}
+
+impl Drop for CommitIter {
+    // stopping early shouldn't leave git running
+    fn drop(&mut self) {
+        if let Some((mut child, _)) = self.child.take() {
+            let _ = child.kill();
+            let _ = child.wait();
+        }
+    }
+}
+
+impl GitCommand {
+    // `GitError::InvalidObject` unless `rev` resolves to a commit, for commands whose own
+    // failure for a bad revision is indistinguishable from their informational exits
+    pub(crate) fn require_commit(&self, rev: &str) -> Result<(), GitError> {
+        let exists = self
+            .fresh()
+            .rev_parse()
+            .arg("--verify")
+            .arg("--quiet")
+            .arg(&format!("{}^{{commit}}", rev))
+            .execute()?
+            .success();
+        if !exists {
+            return Err(GitError::InvalidObject(String::from(rev)));
+        }
+        Ok(())
+    }
+
+    fn commit_log(
+        &self,
+        limit: Option<usize>,
+        order: &[LogOrder],
+        use_mailmap: bool,
+    ) -> GitCommand {
+        let mut cmd = self.fresh_parser();
+        cmd.log()
+            .arg("--no-color")
+            .arg(&commit_format(use_mailmap).to_arg());
+        for flag in order.iter().filter_map(|o| o.flag()) {
+            cmd.arg(flag);
+        }
+        if let Some(n) = limit {
+            cmd.arg(&format!("--max-count={}", n));
+        }
+        cmd
+    }
+
+    // commits reachable from HEAD, newest first unless `order` says otherwise. with
+    // `use_mailmap` authors are reported as `.mailmap` normalizes them
+    pub fn log_commits(
+        &self,
+        limit: Option<usize>,
+        order: &[LogOrder],
+        use_mailmap: bool,
+    ) -> Result<Vec<CommitInfo>, GitError> {
+        parse_commits(&self.commit_log(limit, order, use_mailmap).stdout()?)
+    }
+
+    // `log --first-parent`, following only the mainline through merges
+    pub fn first_parent_log(
+        &self,
+        limit: Option<usize>,
+        order: &[LogOrder],
+    ) -> Result<Vec<CommitInfo>, GitError> {
+        let mut cmd = self.commit_log(limit, order, false);
+        cmd.arg("--first-parent");
+        parse_commits(&cmd.stdout()?)
+    }
+
+    // like `log_commits`, but streamed so callers can stop early without buffering everything
+    pub fn log_commits_iter(&self, order: &[LogOrder]) -> CommitIter {
+        CommitIter::new(&self.commit_log(None, order, false))
+    }
+
+    // history of a single path, following it across renames
+    pub fn file_history(
+        &self,
+        path: &str,
+        limit: Option<usize>,
+        order: &[LogOrder],
+    ) -> Result<Vec<CommitInfo>, GitError> {
+        let mut cmd = self.commit_log(limit, order, false);
+        cmd.arg("--follow").paths(&[path]);
+        parse_commits(&cmd.stdout()?)
+    }
+
+    // lines added and removed per author over `range` (all of HEAD's history by default),
+    // in order of each author's most recent commit, optionally merging `.mailmap` aliases
+    pub fn churn(
+        &self,
+        range: Option<&str>,
+        use_mailmap: bool,
+    ) -> Result<Vec<AuthorChurn>, GitError> {
+        let mut cmd = self.fresh_parser();
+        cmd.log()
+            .arg("--no-color")
+            .arg("--numstat")
+            .arg(if use_mailmap {
+                "--pretty=format:%x1e%aN"
+            } else {
+                "--pretty=format:%x1e%an"
+            });
+        if let Some(range) = range {
+            cmd.arg(range);
+        }
+        parse_churn(&cmd.stdout()?)
+    }
+
+    // `rev-list --count <base>..<head>`, the commits `head` has that `base` doesn't
+    pub fn commits_between(&self, base: &str, head: &str) -> Result<usize, GitError> {
+        for rev in [base, head] {
+            self.require_commit(rev)?;
+        }
+        let count = self
+            .fresh()
+            .rev_list()
+            .arg("--count")
+            .range(base, head)
+            .run_trimmed()?;
+        count
+            .parse()
+            .map_err(|_| GitError::Parse(format!("invalid commit count `{}`", count)))
+    }
+
+    // one entry per commit in `from..to`, each rendered with the pretty `format`;
+    // entries are delimited by 0x1e, so `format` may not produce it itself
+    pub fn changelog(
+        &self,
+        from: &str,
+        to: &str,
+        format: &str,
+        no_merges: bool,
+    ) -> Result<Vec<String>, GitError> {
+        if format.contains('\x1e') || format.to_ascii_lowercase().contains("%x1e") {
+            return Err(GitError::InvalidCommand(String::from(
+                "changelog format may not contain the 0x1e record separator",
+            )));
+        }
+        let mut cmd = self.fresh_parser();
+        cmd.log()
+            .arg("--no-color")
+            .arg(&format!("--pretty=format:{}%x1e", format));
+        if no_merges {
+            cmd.arg("--no-merges");
+        }
+        cmd.range(from, to);
+        Ok(cmd
+            .stdout()?
+            .split('\x1e')
+            .map(|entry| entry.trim_start_matches('\n'))
+            .filter(|entry| !entry.is_empty())
+            .map(String::from)
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_churn_sums_per_author() {
+        let out = "\x1eAda\n3\t1\tsrc/a.rs\n-\t-\tlogo.png\n\n\x1eBob\n2\t0\tREADME\n\n\x1eAda\n1\t4\tsrc/b.rs\n";
+        assert_eq!(
+            parse_churn(out).unwrap(),
+            [
+                AuthorChurn {
+                    author: String::from("Ada"),
+                    insertions: 4,
+                    deletions: 5,
+                },
+                AuthorChurn {
+                    author: String::from("Bob"),
+                    insertions: 2,
+                    deletions: 0,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_churn_rejects_garbage() {
+        assert!(parse_churn("\x1eAda\nthree\t1\tsrc/a.rs\n").is_err());
+        // numstat before any author line
+        assert!(parse_churn("1\t1\tsrc/a.rs\n").is_err());
+    }
+}
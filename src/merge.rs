@@ -0,0 +1,97 @@
+use crate::{trim_output, GitCommand, GitError, Trim};
+
+impl GitCommand {
+    // `merge-base --is-ancestor <ancestor> <descendant>`, unrelated histories are simply `false`
+    pub fn is_ancestor(&self, ancestor: &str, descendant: &str) -> Result<bool, GitError> {
+        let mut cmd = self.fresh();
+        cmd.merge_base()
+            .arg("--is-ancestor")
+            .arg(ancestor)
+            .arg(descendant);
+        let output = cmd.execute()?;
+        match output.code {
+            Some(0) => Ok(true),
+            Some(1) => Ok(false),
+            _ => Err(cmd.failure(&output)),
+        }
+    }
+
+    // whether merging `target` into HEAD would be a fast-forward
+    pub fn can_fast_forward(&self, target: &str) -> Result<bool, GitError> {
+        self.is_ancestor("HEAD", target)
+    }
+
+    // `merge-base <a> <b>`, `None` when the histories are unrelated
+    pub fn merge_base_of(&self, a: &str, b: &str) -> Result<Option<String>, GitError> {
+        let mut cmd = self.fresh();
+        cmd.merge_base().arg(a).arg(b);
+        let output = cmd.execute()?;
+        match output.code {
+            Some(0) => Ok(Some(trim_output(
+                String::from_utf8(output.stdout)?,
+                Trim::Newline,
+            ))),
+            Some(1) => Ok(None),
+            _ => Err(cmd.failure(&output)),
+        }
+    }
+
+    // `merge-base --octopus <revs>`, the best common ancestor of all of `revs` for an n-way
+    // merge, `None` when there is none. fewer than two revisions is an error
+    pub fn merge_base_octopus(&self, revs: &[&str]) -> Result<Option<String>, GitError> {
+        if revs.len() < 2 {
+            return Err(GitError::InvalidCommand(String::from(
+                "merge-base --octopus needs at least two revisions",
+            )));
+        }
+        let mut cmd = self.fresh();
+        cmd.merge_base().arg("--octopus");
+        for rev in revs {
+            cmd.arg(rev);
+        }
+        let output = cmd.execute()?;
+        match output.code {
+            Some(0) => Ok(Some(trim_output(
+                String::from_utf8(output.stdout)?,
+                Trim::Newline,
+            ))),
+            Some(1) => Ok(None),
+            _ => Err(cmd.failure(&output)),
+        }
+    }
+
+    // whether merging `branch` into HEAD would conflict, computed without touching the index
+    // or working tree. git 2.38+ has `merge-tree --write-tree`, which exits 1 on conflicts;
+    // older versions only have the trivial-merge form, whose output has conflict markers
+    pub fn merge_would_conflict(&self, branch: &str) -> Result<bool, GitError> {
+        // merge-tree also exits 1 for a revision it can't resolve
+        self.require_commit(branch)?;
+        if self.git_version()? >= (2, 38, 0) {
+            let mut cmd = self.fresh();
+            cmd.subcommand("merge-tree")
+                .arg("--write-tree")
+                .arg("--name-only")
+                .arg("--no-messages")
+                .arg("HEAD")
+                .arg(branch);
+            let output = cmd.execute()?;
+            return match output.code {
+                Some(0) => Ok(false),
+                Some(1) => Ok(true),
+                _ => Err(cmd.failure(&output)),
+            };
+        }
+        let base = self.merge_base_of("HEAD", branch)?.ok_or_else(|| {
+            GitError::InvalidCommand(format!("HEAD and {} have no common ancestor", branch))
+        })?;
+        let mut cmd = self.fresh();
+        cmd.subcommand("merge-tree")
+            .arg(&base)
+            .arg("HEAD")
+            .arg(branch);
+        Ok(cmd
+            .stdout()?
+            .lines()
+            .any(|line| line.starts_with("+<<<<<<<")))
+    }
+}
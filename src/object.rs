@@ -0,0 +1,101 @@
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use crate::{split_nul, trim_output, GitCommand, GitError, Trim};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ObjectKind {
+    Commit,
+    Tree,
+    Blob,
+    Tag,
+}
+
+impl FromStr for ObjectKind {
+    type Err = GitError;
+
+    fn from_str(s: &str) -> Result<ObjectKind, GitError> {
+        match s {
+            "commit" => Ok(ObjectKind::Commit),
+            "tree" => Ok(ObjectKind::Tree),
+            "blob" => Ok(ObjectKind::Blob),
+            "tag" => Ok(ObjectKind::Tag),
+            other => Err(GitError::Parse(format!("unknown object type `{}`", other))),
+        }
+    }
+}
+
+// parses `ls-tree -r -l -z`, `<mode> <type> <oid> <size>\t<path>` with the size padded;
+// entries without a size (`-`, i.e. submodules) are skipped
+pub(crate) fn parse_tree_sizes(out: &str) -> Result<Vec<(PathBuf, u64)>, GitError> {
+    let mut sizes = Vec::new();
+    for record in split_nul(out) {
+        let (meta, path) = record
+            .split_once('\t')
+            .ok_or_else(|| GitError::Parse(format!("invalid ls-tree entry `{}`", record)))?;
+        let size = meta.split_whitespace().nth(3).unwrap_or_default();
+        if size == "-" {
+            continue;
+        }
+        let size = size
+            .parse()
+            .map_err(|_| GitError::Parse(format!("invalid blob size in `{}`", record)))?;
+        sizes.push((PathBuf::from(path), size));
+    }
+    Ok(sizes)
+}
+
+impl GitCommand {
+    // `cat-file -t <rev>`, an unknown object is reported as `GitError::InvalidObject`. both
+    // that and real failures exit 128, so as in `object_exists` the message decides, under
+    // the C locale: an unresolvable name, or a full id with no object behind it
+    pub fn object_type_of(&self, rev: &str) -> Result<ObjectKind, GitError> {
+        let mut cmd = self.fresh();
+        cmd.env("LC_ALL", "C").cat_file().arg("-t").arg(rev);
+        let output = cmd.execute()?;
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let unknown = stderr.contains("Not a valid object name")
+            || stderr.contains("could not get object info");
+        match output.code {
+            Some(0) => trim_output(String::from_utf8(output.stdout)?, Trim::Newline).parse(),
+            Some(128) if unknown => Err(GitError::InvalidObject(String::from(rev))),
+            _ => Err(cmd.failure(&output)),
+        }
+    }
+
+    // `cat-file -e <rev>`. a missing object exits 1 and an unresolvable name exits 128,
+    // which is also how git reports real failures (e.g. outside a repository), so the
+    // latter is told apart by its message under the C locale
+    pub fn object_exists(&self, rev: &str) -> Result<bool, GitError> {
+        let mut cmd = self.fresh();
+        cmd.env("LC_ALL", "C").cat_file().arg("-e").arg(rev);
+        let output = cmd.execute()?;
+        match output.code {
+            Some(0) => Ok(true),
+            Some(1) => Ok(false),
+            Some(128)
+                if String::from_utf8_lossy(&output.stderr).contains("Not a valid object name") =>
+            {
+                Ok(false)
+            }
+            _ => Err(cmd.failure(&output)),
+        }
+    }
+
+    // every blob in `rev`'s tree with its size in bytes, without checking anything out
+    pub fn tree_file_sizes(&self, rev: &str) -> Result<Vec<(PathBuf, u64)>, GitError> {
+        let mut cmd = self.fresh();
+        cmd.ls_tree().arg("-r").arg("-l").arg("-z").arg(rev);
+        parse_tree_sizes(&cmd.stdout()?)
+    }
+
+    // `hash-object [-w] --stdin`, the blob id of `data`, also storing it when `write` is set
+    pub fn hash_object(&self, data: &[u8], write: bool) -> Result<String, GitError> {
+        let mut cmd = self.fresh();
+        cmd.subcommand("hash-object");
+        if write {
+            cmd.arg("-w");
+        }
+        cmd.arg("--stdin").stdin(data).run_trimmed()
+    }
+}
@@ -0,0 +1,34 @@
+use crate::{GitCommand, GitError, GitOutput};
+
+/*
+ * an ordered list of commands run one after another, like `git a && git b`
+ */
+#[derive(Debug, Default)]
+pub struct GitPipeline {
+    commands: Vec<GitCommand>,
+}
+
+impl GitPipeline {
+    pub fn new() -> GitPipeline {
+        GitPipeline::default()
+    }
+
+    pub fn push(&mut self, cmd: &GitCommand) -> &mut GitPipeline {
+        self.commands.push(cmd.duplicate());
+        self
+    }
+
+    // runs every command in order. with `stop_on_error` the first failure is returned
+    // as an error and nothing after it runs, otherwise failures are kept in the outputs
+    pub fn run_all(&self, stop_on_error: bool) -> Result<Vec<GitOutput>, GitError> {
+        let mut outputs = Vec::new();
+        for cmd in &self.commands {
+            outputs.push(if stop_on_error {
+                cmd.output()?
+            } else {
+                cmd.execute()?
+            });
+        }
+        Ok(outputs)
+    }
+}
@@ -0,0 +1,115 @@
+use crate::{GitCommand, GitError};
+
+/* a single `--pretty` placeholder */
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PrettyField {
+    Hash,
+    ShortHash,
+    TreeHash,
+    // space separated
+    ParentHashes,
+    AuthorName,
+    AuthorEmail,
+    // the author as rewritten by `.mailmap`, `%aN`/`%aE`
+    MailmapAuthorName,
+    MailmapAuthorEmail,
+    // seconds since the epoch
+    AuthorDate,
+    CommitterName,
+    CommitterEmail,
+    // seconds since the epoch
+    CommitterDate,
+    Subject,
+    Body,
+    // `%D`, the ref names pointing at the commit without the surrounding parentheses
+    RefNames,
+}
+
+impl PrettyField {
+    fn placeholder(self) -> &'static str {
+        match self {
+            PrettyField::Hash => "%H",
+            PrettyField::ShortHash => "%h",
+            PrettyField::TreeHash => "%T",
+            PrettyField::ParentHashes => "%P",
+            PrettyField::AuthorName => "%an",
+            PrettyField::AuthorEmail => "%ae",
+            PrettyField::MailmapAuthorName => "%aN",
+            PrettyField::MailmapAuthorEmail => "%aE",
+            PrettyField::AuthorDate => "%at",
+            PrettyField::CommitterName => "%cn",
+            PrettyField::CommitterEmail => "%ce",
+            PrettyField::CommitterDate => "%ct",
+            PrettyField::Subject => "%s",
+            PrettyField::Body => "%b",
+            PrettyField::RefNames => "%D",
+        }
+    }
+}
+
+/* a `--pretty=format:` built from typed fields, separated by 0x1f and terminated by 0x1e,
+together with the parser for the records it produces */
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PrettyFormat {
+    fields: Vec<PrettyField>,
+}
+
+impl PrettyFormat {
+    pub fn new() -> PrettyFormat {
+        PrettyFormat::default()
+    }
+
+    pub fn field(mut self, field: PrettyField) -> PrettyFormat {
+        self.fields.push(field);
+        self
+    }
+
+    pub fn fields(&self) -> &[PrettyField] {
+        &self.fields
+    }
+
+    // the `--pretty=format:...` argument
+    pub fn to_arg(&self) -> String {
+        let placeholders = self
+            .fields
+            .iter()
+            .map(|f| f.placeholder())
+            .collect::<Vec<&str>>();
+        format!("--pretty=format:{}%x1e", placeholders.join("%x1f"))
+    }
+
+    // one record's values, in field order. `record` is what preceded a 0x1e
+    pub(crate) fn split_record(&self, record: &str) -> Result<Vec<String>, GitError> {
+        // `format:` separates records with a newline
+        let record = record.strip_prefix('\n').unwrap_or(record);
+        let values = record
+            .split('\x1f')
+            .map(String::from)
+            .collect::<Vec<String>>();
+        if values.len() != self.fields.len() {
+            return Err(GitError::Parse(format!("invalid log record `{}`", record)));
+        }
+        Ok(values)
+    }
+
+    // every record in `out`, which must have been produced with `to_arg`
+    pub fn parse(&self, out: &str) -> Result<Vec<Vec<String>>, GitError> {
+        out.split_terminator('\x1e')
+            .map(|record| self.split_record(record))
+            .collect()
+    }
+}
+
+impl GitCommand {
+    // `log` rendered with `fmt`, one vector of field values per commit reachable from HEAD
+    pub fn log_custom(&self, fmt: PrettyFormat) -> Result<Vec<Vec<String>>, GitError> {
+        if fmt.fields.is_empty() {
+            return Err(GitError::InvalidCommand(String::from(
+                "a pretty format needs at least one field",
+            )));
+        }
+        let mut cmd = self.fresh_parser();
+        cmd.log().arg("--no-color").arg(&fmt.to_arg());
+        fmt.parse(&cmd.stdout()?)
+    }
+}
@@ -0,0 +1,74 @@
+use crate::{GitCommand, GitError};
+
+// the branches named by `checkout: moving from <from> to <to>` reflog subjects, newest
+// first and each only once, keeping only names in `branches` so detached checkouts of
+// commits and tags (and since deleted branches) are left out
+pub(crate) fn parse_recent_branches(out: &str, branches: &[&str], n: usize) -> Vec<String> {
+    let mut recent: Vec<String> = Vec::new();
+    for line in out.lines() {
+        let Some((from, to)) = line
+            .strip_prefix("checkout: moving from ")
+            .and_then(|moves| moves.split_once(" to "))
+        else {
+            continue;
+        };
+        for name in [to, from] {
+            if recent.len() < n && branches.contains(&name) && !recent.iter().any(|r| r == name) {
+                recent.push(String::from(name));
+            }
+        }
+    }
+    recent
+}
+
+impl GitCommand {
+    // `reflog expire --expire=<expire> [--all] [<ref_name>]`, permanently dropping entries
+    // older than `expire` (`now`, `30.days`, a date). commits only reachable through those
+    // entries become garbage that the next `gc` deletes for good
+    pub fn reflog_expire(
+        &mut self,
+        ref_name: Option<&str>,
+        expire: &str,
+        all: bool,
+    ) -> &mut GitCommand {
+        self.reflog()
+            .arg("expire")
+            .arg(&format!("--expire={}", expire));
+        if all {
+            self.arg("--all");
+        }
+        if let Some(ref_name) = ref_name {
+            self.arg(ref_name);
+        }
+        self
+    }
+
+    // `reflog delete <ref>@{n}`, removing a single entry, equally irreversibly
+    pub fn reflog_delete(&mut self, ref_selector: &str) -> &mut GitCommand {
+        self.reflog().arg("delete").arg(ref_selector)
+    }
+
+    // up to `n` local branches in the order they were last checked out, the current one
+    // first, from HEAD's reflog
+    pub fn recent_branches(&self, n: usize) -> Result<Vec<String>, GitError> {
+        // an unborn HEAD has no reflog to read yet
+        if !self.has_commits()? {
+            return Ok(Vec::new());
+        }
+        let branches = self
+            .fresh()
+            .for_each_ref()
+            .arg("--format=%(refname:short)")
+            .arg("refs/heads")
+            .stdout()?;
+        let branches = branches.lines().collect::<Vec<&str>>();
+        let out = self
+            .fresh_parser()
+            .reflog()
+            .arg("show")
+            .arg("--format=%gs")
+            .arg("HEAD")
+            .stdout()?;
+        Ok(parse_recent_branches(&out, &branches, n))
+    }
+}
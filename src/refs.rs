@@ -0,0 +1,49 @@
+use crate::{trim_output, GitCommand, GitError, Trim};
+
+impl GitCommand {
+    // `update-ref <ref_name> <new_oid> [<old_oid>]`. with `old_oid` the update is a
+    // compare-and-swap: git refuses it (exiting non-zero, "is at X but expected Y") if the
+    // ref moved in the meantime
+    pub fn update_ref(
+        &mut self,
+        ref_name: &str,
+        new_oid: &str,
+        old_oid: Option<&str>,
+    ) -> &mut GitCommand {
+        self.subcommand("update-ref").arg(ref_name).arg(new_oid);
+        if let Some(old) = old_oid {
+            self.arg(old);
+        }
+        self
+    }
+
+    // `update-ref -d <ref_name> [<old_oid>]`, with the same compare-and-swap check
+    pub fn delete_ref(&mut self, ref_name: &str, old_oid: Option<&str>) -> &mut GitCommand {
+        self.subcommand("update-ref").arg("-d").arg(ref_name);
+        if let Some(old) = old_oid {
+            self.arg(old);
+        }
+        self
+    }
+
+    // the ref `name` points at, e.g. `refs/heads/main` for `HEAD`. `-q` makes git exit 1
+    // quietly when `name` isn't symbolic (a detached HEAD), reported as `None`
+    pub fn symbolic_ref(&self, name: &str) -> Result<Option<String>, GitError> {
+        let mut cmd = self.fresh();
+        cmd.subcommand("symbolic-ref").arg("-q").arg(name);
+        let output = cmd.execute()?;
+        match output.code {
+            Some(0) => Ok(Some(trim_output(
+                String::from_utf8(output.stdout)?,
+                Trim::Newline,
+            ))),
+            Some(1) => Ok(None),
+            _ => Err(cmd.failure(&output)),
+        }
+    }
+
+    // `symbolic-ref <name> <target>`, e.g. pointing `HEAD` at another branch without a checkout
+    pub fn set_symbolic_ref(&mut self, name: &str, target: &str) -> &mut GitCommand {
+        self.subcommand("symbolic-ref").arg(name).arg(target)
+    }
+}
@@ -0,0 +1,270 @@
+use crate::{trim_output, GitCommand, GitError, Trim};
+
+// where forges publish review heads, `{number}` is replaced by the PR/MR number
+pub const GITHUB_PR_REFSPEC: &str = "pull/{number}/head";
+pub const GITLAB_MR_REFSPEC: &str = "merge-requests/{number}/head";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PullMode {
+    Merge,
+    Rebase,
+    FastForwardOnly,
+}
+
+impl PullMode {
+    fn flag(self) -> &'static str {
+        match self {
+            PullMode::Merge => "--no-rebase",
+            PullMode::Rebase => "--rebase",
+            PullMode::FastForwardOnly => "--ff-only",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CloneOpts {
+    // `--depth=<n>`, a shallow clone of the last `n` commits
+    pub depth: Option<u32>,
+    // `--branch=<name>`, checked out instead of the remote's HEAD
+    pub branch: Option<String>,
+    // `--single-branch`, only fetching the history of that one branch
+    pub single_branch: bool,
+    // `--filter=<spec>` for a partial clone, e.g. `blob:none` or `tree:0`. the server has
+    // to support the filter capability, otherwise git warns and clones everything
+    pub filter: Option<String>,
+}
+
+// `<oid>\t<ref>` lines, dropping the `<tag>^{}` peeled entries unless asked for
+pub(crate) fn parse_ls_remote(
+    out: &str,
+    include_peeled: bool,
+) -> Result<Vec<(String, String)>, GitError> {
+    let mut refs = Vec::new();
+    for line in out.lines().filter(|line| !line.is_empty()) {
+        let (oid, name) = line
+            .split_once('\t')
+            .ok_or_else(|| GitError::Parse(format!("invalid ls-remote line `{}`", line)))?;
+        if include_peeled || !name.ends_with("^{}") {
+            refs.push((String::from(oid), String::from(name)));
+        }
+    }
+    Ok(refs)
+}
+
+impl GitCommand {
+    // `pull <mode> <remote> <ref>` for the current branch's `@{upstream}`, read from
+    // `branch.<name>.remote` and `branch.<name>.merge`
+    pub fn pull_upstream(&mut self, mode: PullMode) -> Result<&mut GitCommand, GitError> {
+        let branch = self
+            .current_branch()?
+            .ok_or_else(|| GitError::NoUpstream(String::from("HEAD")))?;
+        let remote = self.config_get(&format!("branch.{}.remote", branch))?;
+        let merge = self.config_get(&format!("branch.{}.merge", branch))?;
+        match (remote, merge) {
+            (Some(remote), Some(merge)) => {
+                Ok(self.pull().arg(mode.flag()).arg(&remote).arg(&merge))
+            }
+            _ => Err(GitError::NoUpstream(branch)),
+        }
+    }
+
+    // `remote get-url [--push] <remote>`, `None` when there's no such remote
+    pub fn remote_url(&self, remote: &str, push: bool) -> Result<Option<String>, GitError> {
+        let mut cmd = self.fresh();
+        cmd.remote().arg("get-url");
+        if push {
+            cmd.arg("--push");
+        }
+        cmd.arg(remote);
+        let output = cmd.execute()?;
+        match output.code {
+            Some(0) => Ok(Some(trim_output(
+                String::from_utf8(output.stdout)?,
+                Trim::Newline,
+            ))),
+            // git reports an unknown remote with exit status 2
+            Some(2) => Ok(None),
+            _ => Err(cmd.failure(&output)),
+        }
+    }
+
+    // `fetch <remote> pull/<number>/head:<local_branch>` for a GitHub pull request
+    pub fn fetch_pr(&mut self, remote: &str, number: u64, local_branch: &str) -> &mut GitCommand {
+        self.fetch_pr_with(GITHUB_PR_REFSPEC, remote, number, local_branch)
+    }
+
+    // `fetch_pr` for forges using a different ref layout, e.g. `GITLAB_MR_REFSPEC`
+    pub fn fetch_pr_with(
+        &mut self,
+        refspec_template: &str,
+        remote: &str,
+        number: u64,
+        local_branch: &str,
+    ) -> &mut GitCommand {
+        let source = refspec_template.replace("{number}", &number.to_string());
+        self.fetch()
+            .arg(remote)
+            .arg(&format!("{}:{}", source, local_branch))
+    }
+
+    // `ls-remote --heads <remote> <branch>`, this talks to the remote
+    pub fn remote_branch_exists(&self, remote: &str, branch: &str) -> Result<bool, GitError> {
+        let out = self
+            .fresh()
+            .subcommand("ls-remote")
+            .arg("--heads")
+            .arg(remote)
+            .arg(branch)
+            .stdout()?;
+        let wanted = format!("refs/heads/{}", branch);
+        Ok(parse_ls_remote(&out, false)?
+            .iter()
+            .any(|(_, name)| *name == wanted))
+    }
+
+    // (oid, refname) pairs advertised by `remote`, without peeled tag entries
+    pub fn ls_remote(&self, remote: &str) -> Result<Vec<(String, String)>, GitError> {
+        self.ls_remote_peeled(remote, false)
+    }
+
+    // `ls_remote`, optionally keeping the `<tag>^{}` entries that give the commit
+    // an annotated tag points at
+    pub fn ls_remote_peeled(
+        &self,
+        remote: &str,
+        include_peeled: bool,
+    ) -> Result<Vec<(String, String)>, GitError> {
+        let out = self.fresh().subcommand("ls-remote").arg(remote).stdout()?;
+        parse_ls_remote(&out, include_peeled)
+    }
+
+    // `remote prune <remote>`, dropping remote-tracking refs without fetching
+    pub fn prune_remote(&mut self, remote: &str) -> &mut GitCommand {
+        self.remote().arg("prune").arg(remote)
+    }
+
+    // `remote prune` for every configured remote
+    pub fn prune_all(&mut self) -> Result<&mut GitCommand, GitError> {
+        let remotes = self.fresh().remote().stdout()?;
+        self.remote().arg("prune");
+        for remote in remotes.lines().filter(|r| !r.is_empty()) {
+            self.arg(remote);
+        }
+        Ok(self)
+    }
+
+    // runs `remote prune <remote>` and returns the refs it removed, e.g. `origin/foo`
+    pub fn prune_remote_report(&self, remote: &str) -> Result<Vec<String>, GitError> {
+        // the report lines are translated, so read them in the C locale
+        let out = self
+            .fresh()
+            .env("LC_ALL", "C")
+            .prune_remote(remote)
+            .stdout()?;
+        Ok(out
+            .lines()
+            .filter_map(|line| line.trim_start().strip_prefix("* [pruned] "))
+            .map(String::from)
+            .collect())
+    }
+
+    // `clone [<options>] <url> [<dir>]`. a filter spec containing whitespace, which git
+    // would never accept as one, makes the command fail validation
+    pub fn clone_with(&mut self, url: &str, dir: Option<&str>, opts: CloneOpts) -> &mut GitCommand {
+        self.clone();
+        if let Some(depth) = opts.depth {
+            self.arg(&format!("--depth={}", depth));
+        }
+        if let Some(branch) = &opts.branch {
+            self.arg(&format!("--branch={}", branch));
+        }
+        if opts.single_branch {
+            self.arg("--single-branch");
+        }
+        if let Some(filter) = &opts.filter {
+            if filter.is_empty() || filter.contains(char::is_whitespace) {
+                return self.invalidate(format!("invalid clone filter `{}`", filter));
+            }
+            self.arg(&format!("--filter={}", filter));
+        }
+        self.url(url);
+        if let Some(dir) = dir {
+            self.arg(dir);
+        }
+        self
+    }
+
+    // `fetch --deepen=<depth>`, extending a shallow clone's history by `depth` commits
+    pub fn fetch_deepen(&mut self, depth: u32) -> &mut GitCommand {
+        self.fetch().arg(&format!("--deepen={}", depth))
+    }
+
+    // `fetch --unshallow`, turning a shallow clone into a full one
+    pub fn fetch_unshallow(&mut self) -> &mut GitCommand {
+        self.fetch().arg("--unshallow")
+    }
+
+    // `fetch --depth=1 <remote> <sha>` then `checkout --detach FETCH_HEAD`, building an exact
+    // commit with as little transfer as possible. servers only hand out commits by id when
+    // they allow it (`uploadpack.allowReachableSHA1InWant` or `allowAnySHA1InWant`, or
+    // protocol v2), which is what the fetch step's error points at
+    pub fn checkout_sha_shallow(&self, remote: &str, sha: &str) -> Result<(), GitError> {
+        self.fresh()
+            .fetch()
+            .arg("--depth=1")
+            .url(remote)
+            .arg(sha)
+            .output()
+            .map_err(|e| GitError::StepFailed {
+                step: format!(
+                    "fetching {} from {} (the server may not allow fetching commits by id, \
+                     see uploadpack.allowReachableSHA1InWant)",
+                    sha, remote
+                ),
+                source: Box::new(e),
+            })?;
+        self.fresh()
+            .checkout()
+            .arg("--detach")
+            .arg("FETCH_HEAD")
+            .output()
+            .map_err(|e| GitError::StepFailed {
+                step: format!("checking out {} (it was fetched)", sha),
+                source: Box::new(e),
+            })?;
+        Ok(())
+    }
+
+    // `push --follow-tags`, also pushing annotated tags reachable from the pushed commits
+    pub fn follow_tags(&mut self) -> &mut GitCommand {
+        match self.current_subcommand() {
+            Some("push") => self.arg("--follow-tags"),
+            other => {
+                let msg = format!(
+                    "--follow-tags isn't supported by {}",
+                    other.unwrap_or("git")
+                );
+                self.invalidate(msg)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fetch_deepen_and_unshallow_tokens() {
+        let mut cmd = GitCommand::create(false);
+        assert_eq!(
+            cmd.fetch_deepen(3).dry_run(),
+            ["git", "fetch", "--deepen=3"]
+        );
+        let mut cmd = GitCommand::create(false);
+        assert_eq!(
+            cmd.fetch_unshallow().dry_run(),
+            ["git", "fetch", "--unshallow"]
+        );
+    }
+}
@@ -0,0 +1,328 @@
+use std::path::{Component, Path, PathBuf};
+
+use crate::{trim_output, GitCommand, GitError, Trim};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepoInfo {
+    // the work tree, or the git dir itself for a bare repository
+    pub root: PathBuf,
+    pub git_dir: PathBuf,
+    pub current_branch: Option<String>,
+    // empty on an unborn branch
+    pub head_sha: String,
+    pub is_bare: bool,
+    pub is_detached: bool,
+}
+
+// git's own default for `gc.auto`
+const DEFAULT_GC_AUTO: u64 = 6700;
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RepoHealth {
+    pub loose_objects: u64,
+    // sizes in KiB, as `count-objects -v` reports them
+    pub loose_size: u64,
+    pub packed_objects: u64,
+    pub packs: u64,
+    pub pack_size: u64,
+    // loose objects that are also packed and could simply be deleted
+    pub prune_packable: u64,
+    pub garbage: u64,
+    pub refs: usize,
+    // more loose objects than the gc threshold. never set when the threshold is 0
+    pub needs_gc: bool,
+}
+
+// `count-objects -v`, `key: value` lines
+pub(crate) fn parse_count_objects(out: &str) -> Result<RepoHealth, GitError> {
+    let mut health = RepoHealth::default();
+    for line in out.lines().filter(|line| !line.is_empty()) {
+        let invalid = || GitError::Parse(format!("invalid count-objects line `{}`", line));
+        let (key, value) = line.split_once(": ").ok_or_else(invalid)?;
+        let value = value.parse().map_err(|_| invalid())?;
+        match key {
+            "count" => health.loose_objects = value,
+            "size" => health.loose_size = value,
+            "in-pack" => health.packed_objects = value,
+            "packs" => health.packs = value,
+            "size-pack" => health.pack_size = value,
+            "prune-packable" => health.prune_packable = value,
+            "garbage" => health.garbage = value,
+            _ => {}
+        }
+    }
+    Ok(health)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GitOperation {
+    Merge,
+    Rebase,
+    CherryPick,
+    Revert,
+    Bisect,
+}
+
+// the state files each operation leaves under the git dir, checked in this order
+const OPERATION_MARKERS: [(&str, GitOperation); 6] = [
+    ("rebase-merge", GitOperation::Rebase),
+    ("rebase-apply", GitOperation::Rebase),
+    ("MERGE_HEAD", GitOperation::Merge),
+    ("CHERRY_PICK_HEAD", GitOperation::CherryPick),
+    ("REVERT_HEAD", GitOperation::Revert),
+    ("BISECT_LOG", GitOperation::Bisect),
+];
+
+// `git version 2.39.3 (Apple Git-145)` -> (2, 39, 3), ignoring anything after the numbers
+pub(crate) fn parse_version(out: &str) -> Result<(u32, u32, u32), GitError> {
+    let invalid = || GitError::Parse(format!("unexpected version string `{}`", out.trim_end()));
+    let version = out
+        .trim()
+        .strip_prefix("git version ")
+        .ok_or_else(invalid)?;
+    let mut parts = version.split('.').map(|part| {
+        let digits = part
+            .chars()
+            .take_while(char::is_ascii_digit)
+            .collect::<String>();
+        digits.parse::<u32>().ok()
+    });
+    match (parts.next().flatten(), parts.next().flatten(), parts.next()) {
+        (Some(major), Some(minor), patch) => Ok((major, minor, patch.flatten().unwrap_or(0))),
+        _ => Err(invalid()),
+    }
+}
+
+impl GitCommand {
+    // paths git would use for `names` under the git dir (`rev-parse --git-path`), which
+    // accounts for worktrees and relocated dirs such as `core.hooksPath`
+    pub(crate) fn git_paths(&self, names: &[&str]) -> Result<Vec<PathBuf>, GitError> {
+        let mut cmd = self.fresh_parser();
+        cmd.rev_parse();
+        for name in names {
+            cmd.arg("--git-path").arg(name);
+        }
+        let out = cmd.stdout()?;
+        let base = self.cwd.clone().unwrap_or_default();
+        Ok(out.lines().map(|line| base.join(line)).collect())
+    }
+
+    pub fn git_path(&self, name: &str) -> Result<PathBuf, GitError> {
+        self.git_paths(&[name])?
+            .pop()
+            .ok_or_else(|| GitError::Parse(format!("no path for {}", name)))
+    }
+
+    // where `name` lives under the git dir. only plain relative names are accepted, so the
+    // result can't point outside it
+    fn git_file_path(&self, name: &str) -> Result<PathBuf, GitError> {
+        let escapes = Path::new(name)
+            .components()
+            .any(|c| !matches!(c, Component::Normal(_) | Component::CurDir));
+        if name.is_empty() || escapes {
+            return Err(GitError::PathOutsideRepo(String::from(name)));
+        }
+        self.git_path(name)
+    }
+
+    // the contents of a file under the git dir such as `description` or `info/exclude`,
+    // `None` if it doesn't exist
+    pub fn read_git_file(&self, name: &str) -> Result<Option<Vec<u8>>, GitError> {
+        match std::fs::read(self.git_file_path(name)?) {
+            Ok(data) => Ok(Some(data)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    // replaces a file under the git dir, creating its parent directories as needed
+    pub fn write_git_file(&self, name: &str, data: &[u8]) -> Result<(), GitError> {
+        let path = self.git_file_path(name)?;
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        Ok(std::fs::write(path, data)?)
+    }
+
+    // the merge, rebase, cherry-pick, revert or bisect that is currently stopped, if any
+    pub fn operation_in_progress(&self) -> Result<Option<GitOperation>, GitError> {
+        let names = OPERATION_MARKERS.map(|(name, _)| name);
+        let paths = self.git_paths(&names)?;
+        Ok(paths
+            .iter()
+            .zip(OPERATION_MARKERS)
+            .find(|(path, _)| path.exists())
+            .map(|(_, (_, operation))| operation))
+    }
+
+    // whether HEAD resolves, `false` on an unborn branch such as a freshly initialized repo
+    pub fn has_commits(&self) -> Result<bool, GitError> {
+        let mut cmd = self.fresh();
+        cmd.rev_parse().arg("--verify").arg("--quiet").arg("HEAD");
+        let output = cmd.execute()?;
+        match output.code {
+            Some(0) => Ok(true),
+            Some(1) => Ok(false),
+            _ => Err(cmd.failure(&output)),
+        }
+    }
+
+    // `path`, taken relative to the command's directory (or absolute), as a path from the
+    // repository root. `..` is resolved lexically against `rev-parse --show-prefix`, so
+    // the path needn't exist or be tracked
+    pub fn relative_path(&self, path: &str) -> Result<PathBuf, GitError> {
+        let outside = || GitError::PathOutsideRepo(String::from(path));
+        let given = Path::new(path);
+        let (base, rest) = if given.is_absolute() {
+            let top = self
+                .fresh()
+                .rev_parse()
+                .arg("--show-toplevel")
+                .run_trimmed()?;
+            (
+                PathBuf::new(),
+                given.strip_prefix(top).map_err(|_| outside())?,
+            )
+        } else {
+            let prefix = self
+                .fresh()
+                .rev_parse()
+                .arg("--show-prefix")
+                .run_trimmed()?;
+            (PathBuf::from(prefix), given)
+        };
+        let mut resolved = base;
+        for component in rest.components() {
+            match component {
+                Component::CurDir => {}
+                Component::ParentDir => {
+                    if !resolved.pop() {
+                        return Err(outside());
+                    }
+                }
+                other => resolved.push(other),
+            }
+        }
+        Ok(resolved)
+    }
+
+    // object and ref counts in one call, with `needs_gc` set once there are more loose
+    // objects than `gc_threshold`, or than the repository's `gc.auto` (default 6700) if `None`.
+    // a threshold of 0 disables it, as `gc.auto=0` turns off automatic gc
+    pub fn repo_health(&self, gc_threshold: Option<u64>) -> Result<RepoHealth, GitError> {
+        let mut health = parse_count_objects(
+            &self
+                .fresh()
+                .subcommand("count-objects")
+                .arg("-v")
+                .stdout()?,
+        )?;
+        health.refs = self
+            .fresh()
+            .for_each_ref()
+            .arg("--format=%(refname)")
+            .stdout()?
+            .lines()
+            .count();
+        let threshold = match gc_threshold {
+            Some(threshold) => threshold,
+            None => match self.config_get("gc.auto")? {
+                Some(value) => value
+                    .parse()
+                    .map_err(|_| GitError::Parse(format!("invalid gc.auto `{}`", value)))?,
+                None => DEFAULT_GC_AUTO,
+            },
+        };
+        health.needs_gc = threshold > 0 && health.loose_objects > threshold;
+        Ok(health)
+    }
+
+    pub fn git_version(&self) -> Result<(u32, u32, u32), GitError> {
+        parse_version(&self.fresh().arg("--version").stdout()?)
+    }
+
+    // a summary of the repository, in a single git call for the common case of a
+    // non-bare repository with at least one commit
+    pub fn repo_info(&self) -> Result<RepoInfo, GitError> {
+        let output = self
+            .fresh()
+            .rev_parse()
+            .arg("--is-bare-repository")
+            .arg("--absolute-git-dir")
+            .arg("--show-toplevel")
+            .arg("HEAD")
+            .arg("--abbrev-ref")
+            .arg("HEAD")
+            .execute()?;
+        if output.success() {
+            let out = String::from_utf8(output.stdout)?;
+            let lines = out.lines().collect::<Vec<&str>>();
+            if let [bare, git_dir, root, sha, abbrev] = lines[..] {
+                let is_detached = abbrev == "HEAD";
+                return Ok(RepoInfo {
+                    root: PathBuf::from(root),
+                    git_dir: PathBuf::from(git_dir),
+                    current_branch: (!is_detached).then(|| String::from(abbrev)),
+                    head_sha: String::from(sha),
+                    is_bare: bare == "true",
+                    is_detached,
+                });
+            }
+            return Err(GitError::Parse(format!(
+                "unexpected rev-parse output `{}`",
+                out
+            )));
+        }
+        self.repo_info_slow()
+    }
+
+    // bare repositories and unborn branches make the combined rev-parse fail
+    fn repo_info_slow(&self) -> Result<RepoInfo, GitError> {
+        let out = self
+            .fresh()
+            .rev_parse()
+            .arg("--is-bare-repository")
+            .arg("--absolute-git-dir")
+            .stdout()?;
+        let (is_bare, git_dir) = match out.lines().collect::<Vec<&str>>()[..] {
+            [bare, git_dir] => (bare == "true", PathBuf::from(git_dir)),
+            _ => {
+                return Err(GitError::Parse(format!(
+                    "unexpected rev-parse output `{}`",
+                    out
+                )))
+            }
+        };
+        let root = if is_bare {
+            git_dir.clone()
+        } else {
+            PathBuf::from(
+                self.fresh()
+                    .rev_parse()
+                    .arg("--show-toplevel")
+                    .run_trimmed()?,
+            )
+        };
+        let head = self
+            .fresh()
+            .rev_parse()
+            .arg("--quiet")
+            .arg("--verify")
+            .arg("HEAD")
+            .execute()?;
+        let head_sha = if head.success() {
+            trim_output(String::from_utf8(head.stdout)?, Trim::Newline)
+        } else {
+            String::new()
+        };
+        let current_branch = self.current_branch()?;
+        Ok(RepoInfo {
+            root,
+            git_dir,
+            is_detached: current_branch.is_none(),
+            current_branch,
+            head_sha,
+            is_bare,
+        })
+    }
+}
@@ -0,0 +1,71 @@
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::GitCommand;
+
+/*
+ * a finalized git command, detached from the builder so it can be stored and replayed
+ */
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct SerializableCommand {
+    pub git_cmd: Vec<String>,
+    // how many leading `git_cmd` entries are global options (`-c`, `-C`, ...) rather than
+    // the subcommand and its arguments. absent in records written before it was stored
+    #[serde(default)]
+    pub global_len: usize,
+    pub cwd: Option<PathBuf>,
+    pub env: Vec<(String, String)>,
+    // environment of this command alone, which `then` drops after restoring
+    #[serde(default)]
+    pub command_env: Vec<(String, String)>,
+    pub git_binary: String,
+}
+
+impl GitCommand {
+    pub fn to_serializable(&self) -> SerializableCommand {
+        SerializableCommand {
+            git_cmd: self.git_cmd.clone(),
+            global_len: self.global_len,
+            cwd: self.cwd.clone(),
+            env: self.env.clone(),
+            command_env: self.command_env.clone(),
+            git_binary: self.git_binary.clone(),
+        }
+    }
+
+    pub fn from_serializable(cmd: SerializableCommand) -> GitCommand {
+        let mut git = GitCommand::create(false);
+        git.global_len = cmd.global_len.min(cmd.git_cmd.len());
+        git.git_cmd_started = cmd.git_cmd.len() > git.global_len;
+        git.paths_started = cmd.git_cmd.iter().any(|a| a == "--");
+        git.null_terminated = cmd.git_cmd.iter().any(|a| a == "-z");
+        git.git_cmd = cmd.git_cmd;
+        git.cwd = cmd.cwd;
+        git.env = cmd.env;
+        git.command_env = cmd.command_env;
+        git.git_binary = cmd.git_binary;
+        git
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn round_trip_keeps_global_options() {
+        let mut cmd = GitCommand::create(false);
+        cmd.global_config("core.pager", "cat")
+            .log()
+            .arg("--oneline");
+        let mut restored = GitCommand::from_serializable(cmd.to_serializable());
+        assert_eq!(restored.dry_run(), cmd.dry_run());
+        assert_eq!(restored.current_subcommand(), Some("log"));
+        // `then` keeps the globals and drops the subcommand
+        assert_eq!(
+            restored.then().status().dry_run(),
+            ["git", "-c", "core.pager=cat", "status"]
+        );
+    }
+}
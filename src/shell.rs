@@ -0,0 +1,43 @@
+use crate::GitCommand;
+
+// quotes `arg` for a POSIX shell, leaving plainly safe words untouched
+pub(crate) fn shell_quote(arg: &str) -> String {
+    let safe = !arg.is_empty()
+        && arg
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || "_@%+=:,./-".contains(c));
+    if safe {
+        String::from(arg)
+    } else {
+        format!("'{}'", arg.replace('\'', "'\\''"))
+    }
+}
+
+impl GitCommand {
+    // the command as one line, `cd <cwd> && KEY=value git ...`
+    pub(crate) fn shell_line(&self) -> String {
+        let mut line = String::new();
+        if let Some(dir) = &self.cwd {
+            line += &format!("cd {} && ", shell_quote(&dir.to_string_lossy()));
+        }
+        for (key, value) in self.all_env() {
+            line += &format!("{}={} ", key, shell_quote(value));
+        }
+        line + &self.command()
+    }
+
+    // the command as a snippet that can be pasted into a shell, including the
+    // working directory and environment it would run with
+    pub fn to_shell_script(&self) -> String {
+        let mut script = String::new();
+        if let Some(dir) = &self.cwd {
+            script += &format!("cd {}\n", shell_quote(&dir.to_string_lossy()));
+        }
+        for (key, value) in self.all_env() {
+            script += &format!("{}={} ", key, shell_quote(value));
+        }
+        script += &self.command();
+        script.push('\n');
+        script
+    }
+}
@@ -0,0 +1,84 @@
+use crate::{GitCommand, GitError};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StashEntryFull {
+    // `stash@{N}`, which shifts as stashes are pushed and dropped
+    pub selector: String,
+    // the stash commit, stable for the stash's lifetime
+    pub oid: String,
+    // e.g. `2 hours ago`
+    pub relative_date: String,
+    pub subject: String,
+}
+
+pub(crate) fn stash_selector(index: Option<usize>) -> String {
+    format!("stash@{{{}}}", index.unwrap_or(0))
+}
+
+impl GitCommand {
+    // `stash show -p|--stat stash@{N}`, the latest stash when `index` is `None`
+    pub fn stash_show(&self, index: Option<usize>, stat: bool) -> Result<String, GitError> {
+        let selector = stash_selector(index);
+        let exists = self
+            .fresh()
+            .rev_parse()
+            .arg("--verify")
+            .arg("--quiet")
+            .arg(&selector)
+            .execute()?
+            .success();
+        if !exists {
+            return Err(GitError::NoStashEntry(selector));
+        }
+        self.fresh()
+            .stash()
+            .arg("show")
+            .arg(if stat { "--stat" } else { "-p" })
+            .arg("--no-color")
+            .arg(&selector)
+            .stdout()
+    }
+
+    // `stash list` with each stash's commit hash alongside its selector
+    pub fn stash_entries_detailed(&self) -> Result<Vec<StashEntryFull>, GitError> {
+        let out = self
+            .fresh_parser()
+            .stash()
+            .arg("list")
+            .arg("--format=%gd%x1f%H%x1f%cr%x1f%gs")
+            .stdout()?;
+        out.lines()
+            .filter(|line| !line.is_empty())
+            .map(|line| match line.split('\x1f').collect::<Vec<&str>>()[..] {
+                [selector, oid, relative_date, subject] => Ok(StashEntryFull {
+                    selector: String::from(selector),
+                    oid: String::from(oid),
+                    relative_date: String::from(relative_date),
+                    subject: String::from(subject),
+                }),
+                _ => Err(GitError::Parse(format!("invalid stash record `{}`", line))),
+            })
+            .collect()
+    }
+
+    // `stash create [<message>]`, a stash commit of the current changes that leaves the
+    // working tree, index and stash list alone. `None` when there is nothing to stash
+    pub fn stash_create(&self, message: Option<&str>) -> Result<Option<String>, GitError> {
+        let mut cmd = self.fresh();
+        cmd.stash().arg("create");
+        if let Some(message) = message {
+            cmd.arg(message);
+        }
+        let oid = cmd.run_trimmed()?;
+        Ok(Some(oid).filter(|oid| !oid.is_empty()))
+    }
+
+    // `stash store [-m <message>] <oid>`, adding a `stash_create` commit to the stash list
+    pub fn stash_store(&mut self, oid: &str, message: Option<&str>) -> &mut GitCommand {
+        self.stash().arg("store");
+        if let Some(message) = message {
+            self.arg("-m").arg(message);
+        }
+        self.arg(oid)
+    }
+}
@@ -0,0 +1,244 @@
+use crate::{GitCommand, GitError};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatusEntry {
+    // `X` and `Y` of the porcelain `XY` field, `?` for untracked and `!` for ignored.
+    // unchanged is `.` in v2 output and a space in v1
+    pub index: char,
+    pub worktree: char,
+    pub path: String,
+    // source path of a rename or copy
+    pub orig_path: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatusV2 {
+    // `None` when HEAD is detached
+    pub branch: Option<String>,
+    // `(initial)` on an unborn branch
+    pub oid: String,
+    pub ahead: i64,
+    pub behind: i64,
+    pub entries: Vec<StatusEntry>,
+}
+
+fn parse_xy(xy: &str) -> Result<(char, char), GitError> {
+    let mut chars = xy.chars();
+    match (chars.next(), chars.next(), chars.next()) {
+        (Some(x), Some(y), None) => Ok((x, y)),
+        _ => Err(GitError::Parse(format!("invalid status field `{}`", xy))),
+    }
+}
+
+fn parse_ab(ab: &str) -> Result<(i64, i64), GitError> {
+    let invalid = || GitError::Parse(format!("invalid branch.ab header `{}`", ab));
+    let (ahead, behind) = ab.split_once(' ').ok_or_else(invalid)?;
+    let ahead = ahead.strip_prefix('+').ok_or_else(invalid)?;
+    let behind = behind.strip_prefix('-').ok_or_else(invalid)?;
+    Ok((
+        ahead.parse().map_err(|_| invalid())?,
+        behind.parse().map_err(|_| invalid())?,
+    ))
+}
+
+// parses the output of `status --porcelain=v2 --branch -z`
+pub(crate) fn parse_status_v2(out: &str) -> Result<StatusV2, GitError> {
+    let mut status = StatusV2 {
+        branch: None,
+        oid: String::new(),
+        ahead: 0,
+        behind: 0,
+        entries: Vec::new(),
+    };
+    let mut records = out.split('\0').filter(|r| !r.is_empty());
+    while let Some(record) = records.next() {
+        if let Some(header) = record.strip_prefix("# ") {
+            let (key, value) = header.split_once(' ').unwrap_or((header, ""));
+            match key {
+                "branch.oid" => status.oid = String::from(value),
+                "branch.head" if value != "(detached)" => status.branch = Some(String::from(value)),
+                "branch.ab" => (status.ahead, status.behind) = parse_ab(value)?,
+                _ => {}
+            }
+            continue;
+        }
+        let invalid = || GitError::Parse(format!("invalid status record `{}`", record));
+        let marker_len = record.chars().next().map_or(0, char::len_utf8);
+        let entry = match record.split_at(marker_len) {
+            ("1", rest) => {
+                let fields = rest.trim_start().splitn(8, ' ').collect::<Vec<&str>>();
+                if fields.len() != 8 {
+                    return Err(invalid());
+                }
+                let (index, worktree) = parse_xy(fields[0])?;
+                StatusEntry {
+                    index,
+                    worktree,
+                    path: String::from(fields[7]),
+                    orig_path: None,
+                }
+            }
+            ("2", rest) => {
+                let fields = rest.trim_start().splitn(9, ' ').collect::<Vec<&str>>();
+                if fields.len() != 9 {
+                    return Err(invalid());
+                }
+                let (index, worktree) = parse_xy(fields[0])?;
+                let orig_path = records.next().ok_or_else(invalid)?;
+                StatusEntry {
+                    index,
+                    worktree,
+                    path: String::from(fields[8]),
+                    orig_path: Some(String::from(orig_path)),
+                }
+            }
+            ("u", rest) => {
+                let fields = rest.trim_start().splitn(10, ' ').collect::<Vec<&str>>();
+                if fields.len() != 10 {
+                    return Err(invalid());
+                }
+                let (index, worktree) = parse_xy(fields[0])?;
+                StatusEntry {
+                    index,
+                    worktree,
+                    path: String::from(fields[9]),
+                    orig_path: None,
+                }
+            }
+            (marker @ ("?" | "!"), rest) => {
+                let c = marker.chars().next().unwrap();
+                StatusEntry {
+                    index: c,
+                    worktree: c,
+                    // only the separator, the path itself may start with spaces
+                    path: String::from(rest.strip_prefix(' ').ok_or_else(invalid)?),
+                    orig_path: None,
+                }
+            }
+            _ => return Err(invalid()),
+        };
+        status.entries.push(entry);
+    }
+    Ok(status)
+}
+
+// parses the output of `status --porcelain=v1 -z`
+pub(crate) fn parse_status_v1(out: &str) -> Result<Vec<StatusEntry>, GitError> {
+    let mut entries = Vec::new();
+    let mut records = out.split('\0').filter(|r| !r.is_empty());
+    while let Some(record) = records.next() {
+        let invalid = || GitError::Parse(format!("invalid status record `{}`", record));
+        let mut chars = record.chars();
+        let (index, worktree) = match (chars.next(), chars.next(), chars.next()) {
+            (Some(x), Some(y), Some(' ')) => (x, y),
+            _ => return Err(invalid()),
+        };
+        let orig_path = if index == 'R' || index == 'C' {
+            Some(String::from(records.next().ok_or_else(invalid)?))
+        } else {
+            None
+        };
+        entries.push(StatusEntry {
+            index,
+            worktree,
+            path: String::from(chars.as_str()),
+            orig_path,
+        });
+    }
+    Ok(entries)
+}
+
+impl GitCommand {
+    // `status --porcelain=v1 -z`
+    pub fn status_entries(&self) -> Result<Vec<StatusEntry>, GitError> {
+        self.status_entries_for(&[])
+    }
+
+    // `status_entries` limited to `pathspecs`, e.g. a package directory or `:(exclude)vendor`
+    pub fn status_entries_for(&self, pathspecs: &[&str]) -> Result<Vec<StatusEntry>, GitError> {
+        let mut cmd = self.fresh_parser();
+        cmd.status().arg("--porcelain=v1").arg("-z");
+        if !pathspecs.is_empty() {
+            cmd.paths(pathspecs);
+        }
+        parse_status_v1(&cmd.stdout()?)
+    }
+
+    pub fn status_v2(&self) -> Result<StatusV2, GitError> {
+        let out = self
+            .fresh_parser()
+            .status()
+            .arg("--porcelain=v2")
+            .arg("--branch")
+            .arg("-z")
+            .stdout()?;
+        parse_status_v2(&out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_status_v2_headers_and_entries() {
+        let out = "# branch.oid 1234abcd\0# branch.head main\0# branch.upstream origin/main\0\
+                   # branch.ab +2 -1\0\
+                   1 .M N... 100644 100644 100644 aaaa bbbb src/lib.rs\0\
+                   2 R. N... 100644 100644 100644 aaaa bbbb R100 new name\0old name\0\
+                   ? untracked file\0! ignored\0";
+        let status = parse_status_v2(out).unwrap();
+        assert_eq!(status.branch.as_deref(), Some("main"));
+        assert_eq!(status.oid, "1234abcd");
+        assert_eq!((status.ahead, status.behind), (2, 1));
+        assert_eq!(
+            status.entries,
+            vec![
+                StatusEntry {
+                    index: '.',
+                    worktree: 'M',
+                    path: String::from("src/lib.rs"),
+                    orig_path: None,
+                },
+                StatusEntry {
+                    index: 'R',
+                    worktree: '.',
+                    path: String::from("new name"),
+                    orig_path: Some(String::from("old name")),
+                },
+                StatusEntry {
+                    index: '?',
+                    worktree: '?',
+                    path: String::from("untracked file"),
+                    orig_path: None,
+                },
+                StatusEntry {
+                    index: '!',
+                    worktree: '!',
+                    path: String::from("ignored"),
+                    orig_path: None,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn parse_status_v2_detached_head() {
+        let status = parse_status_v2("# branch.oid 1234abcd\0# branch.head (detached)\0").unwrap();
+        assert_eq!(status.branch, None);
+        assert!(status.entries.is_empty());
+    }
+
+    #[test]
+    fn parse_status_v2_keeps_leading_spaces_in_untracked_paths() {
+        let status = parse_status_v2("?  lead\0!   two\0").unwrap();
+        assert_eq!(status.entries[0].path, " lead");
+        assert_eq!(status.entries[1].path, "  two");
+    }
+
+    #[test]
+    fn parse_status_v2_rejects_truncated_records() {
+        assert!(parse_status_v2("1 .M N...\0").is_err());
+        assert!(parse_status_v2("?\0").is_err());
+    }
+}
@@ -0,0 +1,67 @@
+use std::path::PathBuf;
+
+use crate::{split_nul, GitCommand, GitError};
+
+/* a submodule as declared in `.gitmodules`, whether or not it's been initialized */
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubmoduleInfo {
+    pub name: String,
+    pub path: PathBuf,
+    pub url: Option<String>,
+}
+
+// parses `config -z --get-regexp`, records of `submodule.<name>.<key>\n<value>`. names
+// may themselves contain dots, so the key is split off from the right
+pub(crate) fn parse_submodules(out: &str) -> Result<Vec<SubmoduleInfo>, GitError> {
+    let mut paths: Vec<(String, String)> = Vec::new();
+    let mut urls: Vec<(String, String)> = Vec::new();
+    for record in split_nul(out) {
+        let (key, value) = record.split_once('\n').unwrap_or((&record, ""));
+        let (name, field) = key
+            .strip_prefix("submodule.")
+            .and_then(|k| k.rsplit_once('.'))
+            .ok_or_else(|| GitError::Parse(format!("invalid submodule key `{}`", key)))?;
+        let entry = (String::from(name), String::from(value));
+        match field {
+            "path" => paths.push(entry),
+            "url" => urls.push(entry),
+            _ => {}
+        }
+    }
+    Ok(paths
+        .into_iter()
+        .map(|(name, path)| SubmoduleInfo {
+            url: urls
+                .iter()
+                .find(|(n, _)| *n == name)
+                .map(|(_, url)| url.clone()),
+            path: PathBuf::from(path),
+            name,
+        })
+        .collect())
+}
+
+impl GitCommand {
+    // the submodules `.gitmodules` at the toplevel declares, in file order. a repository
+    // without the file has none
+    pub fn submodules(&self) -> Result<Vec<SubmoduleInfo>, GitError> {
+        let top = self
+            .fresh()
+            .rev_parse()
+            .arg("--show-toplevel")
+            .run_trimmed()?;
+        let mut cmd = self.fresh();
+        cmd.config()
+            .arg("-f")
+            .arg(&PathBuf::from(top).join(".gitmodules").to_string_lossy())
+            .arg("-z")
+            .arg("--get-regexp")
+            .arg(r"^submodule\..*\.(path|url)$");
+        let output = cmd.execute()?;
+        match output.code {
+            Some(0) => parse_submodules(&String::from_utf8(output.stdout)?),
+            Some(1) => Ok(Vec::new()),
+            _ => Err(cmd.failure(&output)),
+        }
+    }
+}
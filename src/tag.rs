@@ -0,0 +1,135 @@
+use crate::{GitCommand, GitError, ObjectKind};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TagInfo {
+    pub name: String,
+    // the object the tag points at, usually a commit
+    pub target_oid: String,
+    // `Name <email>`, only annotated tags have a tagger and message
+    pub tagger: Option<String>,
+    pub message: Option<String>,
+    pub is_annotated: bool,
+}
+
+// parses the headers and message of `cat-file tag <name>`
+pub(crate) fn parse_tag_object(name: &str, out: &str) -> Result<TagInfo, GitError> {
+    let (headers, message) = out.split_once("\n\n").unwrap_or((out, ""));
+    let mut info = TagInfo {
+        name: String::from(name),
+        target_oid: String::new(),
+        tagger: None,
+        message: Some(String::from(message.trim_end())),
+        is_annotated: true,
+    };
+    for line in headers.lines() {
+        if let Some(oid) = line.strip_prefix("object ") {
+            info.target_oid = String::from(oid);
+        } else if let Some(tagger) = line.strip_prefix("tagger ") {
+            // drop the trailing `<timestamp> <tz>`
+            let ident = tagger.rsplitn(3, ' ').nth(2).unwrap_or(tagger);
+            info.tagger = Some(String::from(ident));
+        }
+    }
+    if info.target_oid.is_empty() {
+        return Err(GitError::Parse(format!(
+            "tag {} has no object header",
+            name
+        )));
+    }
+    Ok(info)
+}
+
+impl GitCommand {
+    // metadata for an annotated or lightweight tag
+    pub fn tag_info(&self, name: &str) -> Result<TagInfo, GitError> {
+        let refname = format!("refs/tags/{}", name);
+        if self.object_type_of(&refname)? == ObjectKind::Tag {
+            let out = self
+                .fresh_parser()
+                .cat_file()
+                .arg("tag")
+                .arg(&refname)
+                .stdout()?;
+            return parse_tag_object(name, &out);
+        }
+        let oid = self
+            .fresh()
+            .rev_parse()
+            .arg("--verify")
+            .arg(&refname)
+            .run_trimmed()?;
+        Ok(TagInfo {
+            name: String::from(name),
+            target_oid: oid,
+            tagger: None,
+            message: None,
+            is_annotated: false,
+        })
+    }
+
+    // `push <remote> <tag>`
+    pub fn push_tag(&mut self, remote: &str, tag: &str) -> &mut GitCommand {
+        self.push().arg(remote).arg(tag)
+    }
+
+    // `push <remote> --tags`
+    pub fn push_all_tags(&mut self, remote: &str) -> &mut GitCommand {
+        self.push().arg(remote).arg("--tags")
+    }
+
+    // `push <remote> --delete <tag>`
+    pub fn push_tag_delete(&mut self, remote: &str, tag: &str) -> &mut GitCommand {
+        self.push().arg(remote).arg("--delete").arg(tag)
+    }
+
+    // `tag --contains <rev>`, e.g. which releases shipped a fix
+    pub fn tags_containing(&self, rev: &str) -> Result<Vec<String>, GitError> {
+        let out = self
+            .fresh()
+            .tag()
+            .arg("--no-color")
+            .arg("--contains")
+            .arg(rev)
+            .stdout()?;
+        Ok(out
+            .lines()
+            .filter(|line| !line.is_empty())
+            .map(String::from)
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn push_tag_tokens() {
+        let mut cmd = GitCommand::create(false);
+        assert_eq!(
+            cmd.push_tag("origin", "v1.0").dry_run(),
+            ["git", "push", "origin", "v1.0"]
+        );
+        let mut cmd = GitCommand::create(false);
+        assert_eq!(
+            cmd.push_all_tags("origin").dry_run(),
+            ["git", "push", "origin", "--tags"]
+        );
+        let mut cmd = GitCommand::create(false);
+        assert_eq!(
+            cmd.push_tag_delete("origin", "v1.0").dry_run(),
+            ["git", "push", "origin", "--delete", "v1.0"]
+        );
+    }
+
+    #[test]
+    fn follow_tags_only_on_push() {
+        let mut cmd = GitCommand::create(false);
+        assert_eq!(
+            cmd.push().arg("origin").follow_tags().dry_run(),
+            ["git", "push", "origin", "--follow-tags"]
+        );
+        let mut cmd = GitCommand::create(false);
+        assert!(cmd.fetch().follow_tags().validate().is_err());
+    }
+}
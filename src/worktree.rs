@@ -0,0 +1,69 @@
+use crate::GitCommand;
+
+impl GitCommand {
+    // `worktree lock [--reason=<reason>] <path>`, keeping the worktree from being pruned
+    pub fn worktree_lock(&mut self, path: &str, reason: Option<&str>) -> &mut GitCommand {
+        self.worktree().arg("lock");
+        if let Some(reason) = reason {
+            self.arg(&format!("--reason={}", reason));
+        }
+        self.arg(path)
+    }
+
+    // `worktree unlock <path>`
+    pub fn worktree_unlock(&mut self, path: &str) -> &mut GitCommand {
+        self.worktree().arg("unlock").arg(path)
+    }
+
+    // `worktree move <from> <to>`, relocating a linked worktree along with git's record of
+    // it. an empty path makes the command fail validation
+    pub fn worktree_move(&mut self, from: &str, to: &str) -> &mut GitCommand {
+        self.worktree().arg("move");
+        if from.is_empty() || to.is_empty() {
+            return self.invalidate(String::from("worktree move needs both paths"));
+        }
+        self.arg(from).arg(to)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn worktree_lock_reason_is_one_token() {
+        let mut cmd = GitCommand::create(false);
+        assert_eq!(
+            cmd.worktree_lock("../wt", Some("on a usb drive")).dry_run(),
+            [
+                "git",
+                "worktree",
+                "lock",
+                "--reason=on a usb drive",
+                "../wt"
+            ]
+        );
+        let mut cmd = GitCommand::create(false);
+        assert_eq!(
+            cmd.worktree_lock("../wt", None).dry_run(),
+            ["git", "worktree", "lock", "../wt"]
+        );
+    }
+
+    #[test]
+    fn worktree_move_tokens() {
+        let mut cmd = GitCommand::create(false);
+        assert_eq!(
+            cmd.worktree_move("../old", "../new").dry_run(),
+            ["git", "worktree", "move", "../old", "../new"]
+        );
+    }
+
+    #[test]
+    fn worktree_move_rejects_empty_paths() {
+        let mut cmd = GitCommand::create(false);
+        assert!(cmd.worktree_move("", "../new").validate().is_err());
+        let mut cmd = GitCommand::create(false);
+        assert!(cmd.worktree_move("../old", "").validate().is_err());
+    }
+}